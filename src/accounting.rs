@@ -0,0 +1,298 @@
+use crate::tag::Tag;
+use crate::transaction::Transaction;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::str::FromStr;
+
+// how budget lines up with plain-text accounting: every tag is an account
+// under the expense account, named by capitalizing each of its components,
+// and the money comes from the funding account
+pub struct Accounts<'a> {
+    pub expenses: &'a str,
+    pub funding: &'a str,
+    pub commodity: &'a str,
+}
+
+impl<'a> Accounts<'a> {
+    // e.g. food.fast-food is Expenses:Food:Fast-Food
+    pub fn of(&self, tag: &Tag) -> String {
+        let mut account = self.expenses.to_string();
+        for component in tag.to_dotted_string().split('.').filter(|c| !c.is_empty()) {
+            account.push(':');
+            account.push_str(&capitalize(component));
+        }
+
+        return account;
+    }
+
+    // the comment is the payee, unless it would be read as something else:
+    // a leading * or ! is a flag, a leading ( starts a code and a ; starts a
+    // note. such a comment goes in a note of its own, under the tag as payee
+    pub fn ledger(&self, t: &Transaction) -> String {
+        let comment = one_line(&t.comment);
+        let is_payee =
+            !comment.starts_with(['*', '!', '(']) && !comment.contains(';') && comment == t.comment;
+        let (payee, note) = match (comment.as_str(), is_payee) {
+            ("", _) => (t.tag.to_dotted_string(), String::new()),
+            (_, true) => (comment, String::new()),
+            (_, false) => (
+                t.tag.to_dotted_string(),
+                format!("    ; note: {}\n", comment),
+            ),
+        };
+        let amount = t.amount.to_plain_string();
+
+        return format!(
+            "{} {}\n{}    ; id: {}\n    {}  {} {}\n    {}  {} {}\n",
+            t.date,
+            payee,
+            note,
+            t.id,
+            self.of(&t.tag),
+            amount,
+            self.commodity,
+            self.funding,
+            negate(&amount),
+            self.commodity
+        );
+    }
+
+    // beancount wants every account opened before it is used, so these come
+    // first, dated on the first transaction
+    pub fn beancount_open(&self, transactions: &[Transaction]) -> Result<String, Box<dyn Error>> {
+        let Some(first) = transactions.iter().map(|t| t.date).min() else {
+            return Ok(String::new());
+        };
+
+        self.check_beancount()?;
+        let mut accounts = BTreeSet::new();
+        accounts.insert(self.funding.to_string());
+        for t in transactions {
+            let account = self.of(&t.tag);
+            if !is_beancount_account(&account) {
+                return Err(format!("{} is not a valid beancount account", account).into());
+            }
+            accounts.insert(account);
+        }
+
+        let mut open = String::new();
+        for account in accounts {
+            open.push_str(&format!("{} open {}\n", first, account));
+        }
+
+        return Ok(open);
+    }
+
+    pub fn beancount(&self, t: &Transaction) -> String {
+        let amount = t.amount.to_plain_string();
+
+        return format!(
+            "{} * \"{}\"\n  id: \"{}\"\n  {}  {} {}\n  {}  {} {}\n",
+            t.date,
+            one_line(&t.comment)
+                .replace('\\', "\\\\")
+                .replace('"', "\\\""),
+            t.id,
+            self.of(&t.tag),
+            amount,
+            self.commodity,
+            self.funding,
+            negate(&amount),
+            self.commodity
+        );
+    }
+
+    fn check_beancount(&self) -> Result<(), Box<dyn Error>> {
+        let is_currency = self.commodity.starts_with(|c: char| c.is_ascii_uppercase())
+            && self
+                .commodity
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || "'._-".contains(c));
+        if !is_currency {
+            return Err(format!(
+                "beancount needs a currency such as USD, not {}; see budget config set export.commodity",
+                self.commodity
+            )
+            .into());
+        }
+
+        for account in [self.expenses, self.funding] {
+            if !is_beancount_account(account) {
+                return Err(format!("{} is not a valid beancount account", account).into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// the other way around: Expenses:Food:Fast Food under the prefix Expenses is
+// food.fast-food. accounts outside the prefix, and the prefix itself, have no
+// tag
+pub fn tag_of(account: &str, prefix: &str) -> Result<Option<Tag>, String> {
+    let Some(rest) = account.strip_prefix(prefix) else {
+        return Ok(None);
+    };
+    if !prefix.ends_with(':') && !rest.is_empty() && !rest.starts_with(':') {
+        return Ok(None);
+    }
+
+    let rest = rest.trim_start_matches(':');
+    if rest.is_empty() {
+        return Ok(None);
+    }
+
+    let mut components = Vec::new();
+    for component in rest.split(':') {
+        let component: String = component
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join("-")
+            .chars()
+            .filter(|c| c.is_alphanumeric() || *c == '-')
+            .collect::<String>()
+            .to_lowercase();
+        if component.is_empty() {
+            return Err(format!("{} has a component that makes no tag", account));
+        }
+        components.push(component);
+    }
+
+    return Tag::from_str(&components.join("."))
+        .map(Some)
+        .map_err(|e| format!("{}: {}", account, e));
+}
+
+fn capitalize(component: &str) -> String {
+    component
+        .split('-')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+fn negate(amount: &str) -> String {
+    match amount.strip_prefix('-') {
+        Some(positive) => positive.to_string(),
+        None => format!("-{}", amount),
+    }
+}
+
+// beancount only takes its five root accounts, and components that start
+// with a capital letter or a digit
+fn is_beancount_account(account: &str) -> bool {
+    let mut components = account.split(':');
+    let root = components.next().unwrap_or_default();
+    if !["Assets", "Liabilities", "Equity", "Income", "Expenses"].contains(&root) {
+        return false;
+    }
+
+    return components.all(|c| {
+        c.starts_with(|c: char| c.is_uppercase() || c.is_ascii_digit())
+            && c.chars().all(|c| c.is_alphanumeric() || c == '-')
+    });
+}
+
+// line breaks end an entry's first line, so they become spaces
+fn one_line(comment: &str) -> String {
+    comment.replace("\r\n", " ").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNTS: Accounts = Accounts {
+        expenses: "Expenses",
+        funding: "Assets:Checking",
+        commodity: "USD",
+    };
+
+    fn lunch(comment: &str) -> Transaction {
+        Transaction::sample("2025-09-12", 4_512, "food.fast-food", comment).with_id("0000000a")
+    }
+
+    #[test]
+    fn test_accounts_of_tags() {
+        assert_eq!(
+            ACCOUNTS.of(&Tag::from_str("food.fast-food").unwrap()),
+            "Expenses:Food:Fast-Food"
+        );
+        assert_eq!(ACCOUNTS.of(&Tag::from_str(".").unwrap()), "Expenses");
+    }
+
+    #[test]
+    fn test_tags_of_accounts() {
+        let tag = |s| Some(Tag::from_str(s).unwrap());
+        assert_eq!(
+            tag_of("Expenses:Food:Fast Food", "Expenses:"),
+            Ok(tag("food.fast-food"))
+        );
+        assert_eq!(
+            tag_of("Expenses:Food:Fast-Food", "Expenses"),
+            Ok(tag("food.fast-food"))
+        );
+        assert_eq!(tag_of("ExpensesX:Food", "Expenses"), Ok(None));
+        assert_eq!(tag_of("Expenses", "Expenses:"), Ok(None));
+        assert_eq!(tag_of("Assets:Checking", "Expenses:"), Ok(None));
+        assert!(tag_of("Expenses:Food:&", "Expenses:").is_err());
+    }
+
+    #[test]
+    fn test_entries_balance() {
+        assert_eq!(
+            ACCOUNTS.ledger(&lunch("")),
+            "2025-09-12 food.fast-food\n    ; id: 0000000a\n    \
+             Expenses:Food:Fast-Food  45.12 USD\n    Assets:Checking  -45.12 USD\n"
+        );
+        assert_eq!(
+            ACCOUNTS.beancount(&lunch("the \"usual\"")),
+            "2025-09-12 * \"the \\\"usual\\\"\"\n  id: \"0000000a\"\n  \
+             Expenses:Food:Fast-Food  45.12 USD\n  Assets:Checking  -45.12 USD\n"
+        );
+    }
+
+    #[test]
+    fn test_comments_that_are_not_payees() {
+        assert_eq!(
+            ACCOUNTS.ledger(&lunch("with Sam")).lines().next(),
+            Some("2025-09-12 with Sam")
+        );
+        for comment in ["(refund)", "* starred", "lunch; dinner", "two\nlines"] {
+            let entry = ACCOUNTS.ledger(&lunch(comment));
+            let mut lines = entry.lines();
+            assert_eq!(lines.next(), Some("2025-09-12 food.fast-food"));
+            assert_eq!(
+                lines.next(),
+                Some(format!("    ; note: {}", comment.replace('\n', " ")).as_str())
+            );
+            assert_eq!(entry.lines().count(), 5);
+        }
+
+        assert_eq!(
+            ACCOUNTS.beancount(&lunch("two\r\nlines")).lines().next(),
+            Some("2025-09-12 * \"two lines\"")
+        );
+    }
+
+    #[test]
+    fn test_beancount_open() {
+        let open = ACCOUNTS.beancount_open(&[lunch("")]).unwrap();
+        assert_eq!(
+            open,
+            "2025-09-12 open Assets:Checking\n2025-09-12 open Expenses:Food:Fast-Food\n"
+        );
+
+        let dollars = Accounts {
+            commodity: "$",
+            ..ACCOUNTS
+        };
+        assert!(dollars.beancount_open(&[lunch("")]).is_err());
+    }
+}
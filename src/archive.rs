@@ -0,0 +1,108 @@
+use crate::date::{Date, Month};
+use crate::limits::Limits;
+use crate::transaction::{Transaction, Transactions};
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// each closed month is kept in its own directory, e.g. archive/2025-08/, which
+// holds the transactions.json and limits.json that were in force that month
+pub struct Archive {
+    root: PathBuf,
+}
+
+impl Archive {
+    pub fn open<P>(root: P) -> Archive
+    where
+        P: AsRef<Path>,
+    {
+        Archive {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    // sorted from oldest to newest; directories that are not named after a
+    // month are ignored
+    pub fn months(&self) -> Result<Vec<Month>, io::Error> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut months = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+
+            if let Some(month) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| Month::from_str(name).ok())
+            {
+                months.push(month);
+            }
+        }
+
+        months.sort();
+        return Ok(months);
+    }
+
+    // the most recent `count` archived months strictly before `month`
+    pub fn months_before(&self, month: Month, count: usize) -> Result<Vec<Month>, io::Error> {
+        let mut months = self.months()?;
+        months.retain(|m| *m < month);
+        let skip = months.len().saturating_sub(count);
+
+        return Ok(months.split_off(skip));
+    }
+
+    pub fn transactions(&self, month: Month) -> Result<Transactions, Box<dyn Error>> {
+        Transactions::from_json_file(self.transactions_path(month))
+    }
+
+    pub fn transactions_path(&self, month: Month) -> PathBuf {
+        self.month_path(month).join("transactions.json")
+    }
+
+    // the transactions of every archived month that overlaps the bounds; the
+    // transactions themselves are not filtered
+    pub fn transactions_between(
+        &self,
+        from: Option<Date>,
+        to: Option<Date>,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>> {
+        let mut transactions = Vec::new();
+
+        for month in self.months()? {
+            let before_range = to.is_some_and(|to| month.first_day() > to);
+            let after_range = from.is_some_and(|from| month.last_day() < from);
+            if before_range || after_range {
+                continue;
+            }
+
+            transactions.append(&mut self.transactions(month)?.transactions);
+        }
+
+        return Ok(transactions);
+    }
+
+    pub fn contains(&self, month: Month) -> bool {
+        self.month_path(month).is_dir()
+    }
+
+    // the limits that were in force when the month was closed
+    pub fn limits(&self, month: Month) -> Result<Limits, Box<dyn Error>> {
+        Limits::from_json_file_or_empty(self.limits_path(month))
+    }
+
+    pub fn limits_path(&self, month: Month) -> PathBuf {
+        self.month_path(month).join("limits.json")
+    }
+
+    fn month_path(&self, month: Month) -> PathBuf {
+        self.root.join(month.to_string())
+    }
+}
@@ -0,0 +1,111 @@
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// replaces the file at path with contents so that a crash or a full disk
+// leaves either the old or the new version in place, never a mix. the old
+// version is kept next to it as <path>.bak, replacing the previous backup.
+// the temporary file is removed again whatever goes wrong
+pub fn write<P>(path: P, contents: &[u8]) -> io::Result<()>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let temporary = with_suffix(path, ".tmp");
+
+    let result = replace(path, &temporary, contents);
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+
+    return result;
+}
+
+fn replace(path: &Path, temporary: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(temporary)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    if path.exists() {
+        let backup = backup_path(path);
+        fs::copy(path, &backup)?;
+        File::open(&backup)?.sync_all()?;
+    }
+
+    fs::rename(temporary, path)?;
+    sync_directory(path)?;
+
+    Ok(())
+}
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+// a backup that later writes leave alone, e.g. transactions.json.v1.bak for
+// the file as it was before being upgraded from version 1
+pub fn version_backup_path(path: &Path, version: u32) -> PathBuf {
+    with_suffix(path, &format!(".v{}.bak", version))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+
+    return PathBuf::from(name);
+}
+
+// the rename only survives a crash once the directory itself is synced
+#[cfg(unix)]
+fn sync_directory(path: &Path) -> io::Result<()> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    File::open(directory)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_directory(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scratch::ScratchDir;
+
+    #[test]
+    fn test_write_keeps_a_backup() {
+        let directory = ScratchDir::new("atomic");
+        let path = directory.join("transactions.json");
+
+        write(&path, b"first").unwrap();
+        assert!(!backup_path(&path).exists());
+
+        write(&path, b"second").unwrap();
+        write(&path, b"third").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"third");
+        assert_eq!(fs::read(backup_path(&path)).unwrap(), b"second");
+        assert!(!with_suffix(&path, ".tmp").exists());
+    }
+
+    #[test]
+    fn test_failed_backup_leaves_no_temporary_file() {
+        let directory = ScratchDir::new("atomic-failed");
+        let path = directory.join("transactions.json");
+        write(&path, b"first").unwrap();
+        fs::create_dir(backup_path(&path)).unwrap();
+
+        assert!(write(&path, b"second").is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+        assert!(!with_suffix(&path, ".tmp").exists());
+    }
+}
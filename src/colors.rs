@@ -1,4 +1,32 @@
-pub const RED: &str = "\x1b[38;2;255;0;0m";
-pub const GREEN: &str = "\x1b[38;2;0;255;0m";
-pub const YELLOW: &str = "\x1b[38;2;255;255;0m";
-pub const RESET: &str = "\x1b[0m";
+use crate::settings;
+
+const RED: &str = "\x1b[38;2;255;0;0m";
+const GREEN: &str = "\x1b[38;2;0;255;0m";
+const YELLOW: &str = "\x1b[38;2;255;255;0m";
+const RESET: &str = "\x1b[0m";
+
+// colors can be turned off with display.color, or with NO_COLOR as usual
+fn enabled(color: &'static str) -> &'static str {
+    let disabled = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    if settings::get().display.color && !disabled {
+        color
+    } else {
+        ""
+    }
+}
+
+pub fn red() -> &'static str {
+    enabled(RED)
+}
+
+pub fn green() -> &'static str {
+    enabled(GREEN)
+}
+
+pub fn yellow() -> &'static str {
+    enabled(YELLOW)
+}
+
+pub fn reset() -> &'static str {
+    enabled(RESET)
+}
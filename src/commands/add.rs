@@ -1,40 +1,144 @@
 use std::error::Error;
-use std::str::FromStr;
-use std::{env::Args, fs::File};
 
-use crate::arg_parsers;
-use crate::commands::CommandError;
 use crate::date::Date;
+use crate::journal::{self, Entry};
+use crate::limits::Limits;
+use crate::lock::DataLock;
 use crate::money::Money;
+use crate::paths::Paths;
+use crate::period::Period;
+use crate::progress::{Progress, Standing};
+use crate::settings;
+use crate::storage;
 use crate::tag::Tag;
 use crate::transaction::{Transaction, Transactions};
-use std::fs::OpenOptions;
-use std::io::BufWriter;
+use crate::transaction_tree::TransactionTree;
 
 pub fn add(
+    paths: &Paths,
     tag: Tag,
     amount: Money,
     date: Date,
     comment: Option<String>,
+    strict: bool,
 ) -> Result<(), Box<dyn Error>> {
-    let file = File::open_buffered("transactions.json")?;
-    let mut transactions = Transactions::from_json_reader(file)?;
+    let lock = DataLock::acquire(paths.lock())?;
+    let mut storage = storage::open_for_change(paths, &lock)?;
+    let mut transactions = storage.load()?;
+    let limits = storage.limits()?;
+
+    let period = settings::get().budget.period.period_of(date);
+    let today = Date::today();
+    let before = chain_status(&transactions, &limits, &tag, period, today)?;
 
     let transaction = Transaction {
-        tag,
+        tag: tag.clone(),
         amount,
         comment: comment.unwrap_or("".to_string()),
         date,
+        id: transactions.new_id(),
+        bank_id: None,
     };
 
-    transactions.add(transaction);
+    transactions.add(transaction.clone());
+
+    let after = chain_status(&transactions, &limits, &tag, period, today)?;
+
+    let mut problems = Vec::new();
+    for ((chain_tag, before), (_, after)) in before.iter().zip(after.iter()) {
+        match after {
+            ChainStatus::Limited(progress) => println!(
+                "{}: {} left of {} ({})",
+                chain_tag.as_slice(),
+                progress.remaining().ok_or("remaining amount overflowed")?,
+                progress.limit(),
+                progress.standing()
+            ),
+            ChainStatus::Unlimited(spent) => {
+                println!("{}: {} spent", chain_tag.as_slice(), spent)
+            }
+        }
+
+        let was = before.standing();
+        let is = after.standing();
+        if is > was {
+            match is {
+                Standing::OverLimit => {
+                    problems.push(format!("this puts {} over its limit", chain_tag.as_slice()))
+                }
+                Standing::AheadOfPace => {
+                    problems.push(format!("this puts {} ahead of pace", chain_tag.as_slice()))
+                }
+                Standing::OnTrack => {}
+            }
+        }
+    }
 
-    let file = OpenOptions::new()
-        .write(true)
-        .truncate(true)
-        .open("transactions.json")?;
-    let file = BufWriter::new(file);
-    transactions.to_json_writer(file)?;
+    if strict && !problems.is_empty() {
+        return Err(format!(
+            "{}; the transaction was not added because of --strict",
+            problems.join("; ")
+        )
+        .into());
+    }
+
+    for problem in &problems {
+        println!("warning: {}", problem);
+    }
+
+    storage.append(transaction.clone())?;
+    journal::record(
+        paths.journal(),
+        Entry::transactions(
+            format!("add {}", journal::describe(&transaction)),
+            Vec::new(),
+            vec![transaction],
+        ),
+    )?;
 
     Ok(())
 }
+
+enum ChainStatus {
+    Limited(Progress),
+    Unlimited(Money),
+}
+
+impl ChainStatus {
+    fn standing(&self) -> Standing {
+        match self {
+            ChainStatus::Limited(progress) => progress.standing(),
+            ChainStatus::Unlimited(_) => Standing::OnTrack,
+        }
+    }
+}
+
+// the tag and each of its parents, from the tag itself up to the root
+fn chain_status(
+    transactions: &Transactions,
+    limits: &Limits,
+    tag: &Tag,
+    period: Period,
+    as_of: Date,
+) -> Result<Vec<(Tag, ChainStatus)>, Box<dyn Error>> {
+    let tree = TransactionTree::from_transactions_and_limits(
+        &transactions.transactions,
+        &limits.limits,
+        period,
+        as_of,
+    )?;
+
+    let tag = tag.as_slice();
+    let chain = std::iter::once(tag).chain(tag.parents());
+
+    return Ok(chain
+        .map(|t| {
+            let status = match tree.progress(t) {
+                Some(progress) => ChainStatus::Limited(progress),
+                None => ChainStatus::Unlimited(tree.spent(t).unwrap_or(Money::from_cents(0))),
+            };
+
+            (t.to_tag(), status)
+        })
+        .collect());
+}
@@ -0,0 +1,29 @@
+use std::error::Error;
+
+use crate::lock::DataLock;
+use crate::paths::Paths;
+use crate::settings::Settings;
+
+pub fn get(paths: &Paths, key: Option<String>) -> Result<(), Box<dyn Error>> {
+    let settings = Settings::from_toml_file_or_default(paths.settings())?;
+
+    match key {
+        Some(key) => println!("{}", settings.get(&key)?),
+        None => print!("{}", toml::to_string_pretty(&settings)?),
+    }
+
+    Ok(())
+}
+
+pub fn set(paths: &Paths, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+    let _lock = DataLock::acquire(paths.lock())?;
+    let settings = Settings::from_toml_file_or_default(paths.settings())?;
+    let previous = settings.get(key)?;
+
+    let settings = settings.set(key, value)?;
+    settings.to_toml_file(paths.settings())?;
+
+    println!("{}: {} (was {})", key, settings.get(key)?, previous);
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use std::error::Error;
+
+use crate::commands::select::{by_hand, check_unchanged, select_one};
+use crate::date::Date;
+use crate::import;
+use crate::journal::{self, Entry};
+use crate::lock::DataLock;
+use crate::money::Money;
+use crate::paths::Paths;
+use crate::storage;
+use crate::tag::Tag;
+use crate::transaction::{Selector, Transaction};
+
+// the new values; anything left as None is kept as it was
+pub struct Changes {
+    pub date: Option<Date>,
+    pub amount: Option<Money>,
+    pub tag: Option<Tag>,
+    pub comment: Option<String>,
+}
+
+pub fn edit(paths: &Paths, selector: Selector, changes: Changes) -> Result<(), Box<dyn Error>> {
+    let transactions = storage::open(paths).load()?;
+    let index = select_one(
+        &transactions,
+        &selector,
+        "edit",
+        &by_hand(paths, "edit the transaction in"),
+    )?;
+
+    let before = transactions.transactions[index].clone();
+
+    let lock = DataLock::acquire(paths.lock())?;
+    let mut storage = storage::open_for_change(paths, &lock)?;
+    let transactions = storage.load()?;
+    check_unchanged(&transactions, std::slice::from_ref(&before))?;
+
+    let mut after = before.clone();
+    if let Some(date) = changes.date {
+        after.date = date;
+    }
+    if let Some(amount) = changes.amount {
+        after.amount = amount;
+    }
+    if let Some(tag) = changes.tag {
+        after.tag = tag;
+    }
+    if let Some(comment) = changes.comment {
+        after.comment = comment;
+    }
+
+    // the same rule add and import keep to: only leaves have transactions
+    if after.tag != before.tag {
+        let others: Vec<Transaction> = transactions
+            .transactions
+            .iter()
+            .filter(|t| t.id != before.id)
+            .cloned()
+            .collect();
+        import::check_tags(&others, &storage.limits()?, std::slice::from_ref(&after))?;
+    }
+
+    println!("before: {}: {}", before.tag, before);
+    println!("after:  {}: {}", after.tag, after);
+
+    storage.update(after.clone())?;
+    journal::record(
+        paths.journal(),
+        Entry::transactions(
+            format!("edit {}", journal::describe(&after)),
+            vec![before],
+            vec![after],
+        ),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scratch::ScratchDir;
+    use crate::transaction::{TransactionId, Transactions};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_new_tag_must_be_a_leaf() {
+        let directory = ScratchDir::new("edit");
+        let paths = Paths::new(directory.path());
+
+        let transaction =
+            |tag: &str, id: &str| Transaction::sample("2025-09-12", 300, tag, "").with_id(id);
+        let transactions = Transactions {
+            transactions: vec![
+                transaction("food.coffee", "0000000a"),
+                transaction("food.groceries", "0000000b"),
+            ],
+        };
+        transactions.to_json_file(paths.transactions()).unwrap();
+
+        let changes = |tag: &str| Changes {
+            date: None,
+            amount: None,
+            tag: Some(Tag::from_str(tag).unwrap()),
+            comment: None,
+        };
+        let selector = || Selector {
+            id: Some(TransactionId::from_str("0000000a").unwrap()),
+            tag: None,
+            amount: None,
+            date: None,
+            comment: None,
+        };
+
+        // food is the parent of food.groceries, so it cannot have one
+        assert!(edit(&paths, selector(), changes("food")).is_err());
+        let saved = Transactions::from_json_file(paths.transactions()).unwrap();
+        assert_eq!(saved.transactions, transactions.transactions);
+
+        edit(&paths, selector(), changes("food.tea")).unwrap();
+        let saved = Transactions::from_json_file(paths.transactions()).unwrap();
+        let index = saved.position(&selector().id.unwrap()).unwrap();
+        assert_eq!(
+            saved.transactions[index].tag,
+            Tag::from_str("food.tea").unwrap()
+        );
+    }
+}
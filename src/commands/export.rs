@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::io::{self, Write};
+
+use crate::accounting::Accounts;
+use crate::archive::Archive;
+use crate::commands::status;
+use crate::csv;
+use crate::date::Date;
+use crate::filter::Filter;
+use crate::paths::Paths;
+use crate::period::Period;
+use crate::settings;
+use crate::storage;
+use crate::transaction::Transaction;
+use crate::transaction_tree::TransactionTree;
+
+// the matching transactions, current and archived, in date order. an id only
+// picks among the current ones, as it does for list
+fn transactions(paths: &Paths, filter: &Filter) -> Result<Vec<Transaction>, Box<dyn Error>> {
+    let mut transactions = storage::open(paths).range(filter.from, filter.to)?;
+    if filter.id.is_none() {
+        let archive = Archive::open(paths.archive());
+        transactions.append(&mut archive.transactions_between(filter.from, filter.to)?);
+    }
+
+    transactions.retain(|t| filter.matches(t));
+    transactions.sort();
+
+    return Ok(transactions);
+}
+
+pub fn csv(paths: &Paths, filter: Filter, summary: bool) -> Result<(), Box<dyn Error>> {
+    if summary {
+        return csv_summary(paths, filter);
+    }
+
+    let mut out = io::stdout().lock();
+    csv::write_record(
+        &mut out,
+        &["date", "tag", "amount", "comment", "id", "bank_id"],
+    )?;
+    for t in transactions(paths, &filter)? {
+        csv::write_record(
+            &mut out,
+            &[
+                &t.date.to_string(),
+                &t.tag.to_dotted_string(),
+                &t.amount.to_plain_string(),
+                &t.comment,
+                &t.id.to_string(),
+                t.bank_id.as_deref().unwrap_or(""),
+            ],
+        )?;
+    }
+    out.flush()?;
+
+    Ok(())
+}
+
+fn accounts(settings: &settings::Settings) -> Accounts<'_> {
+    Accounts {
+        expenses: &settings.export.expense_account,
+        funding: &settings.export.funding_account,
+        commodity: &settings.export.commodity,
+    }
+}
+
+// each transaction as a balanced entry, for ledger and hledger
+pub fn ledger(paths: &Paths, filter: Filter) -> Result<(), Box<dyn Error>> {
+    let settings = settings::get();
+    let accounts = accounts(&settings);
+
+    let mut out = io::stdout().lock();
+    for (i, t) in transactions(paths, &filter)?.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        write!(out, "{}", accounts.ledger(t))?;
+    }
+    out.flush()?;
+
+    Ok(())
+}
+
+pub fn beancount(paths: &Paths, filter: Filter) -> Result<(), Box<dyn Error>> {
+    let settings = settings::get();
+    let accounts = accounts(&settings);
+    let transactions = transactions(paths, &filter)?;
+
+    let mut out = io::stdout().lock();
+    write!(out, "{}", accounts.beancount_open(&transactions)?)?;
+    for t in &transactions {
+        write!(out, "\n{}", accounts.beancount(t))?;
+    }
+    out.flush()?;
+
+    Ok(())
+}
+
+// what status shows, one row per tag. with --from and --to the limits are
+// those of the range, as in budget status; otherwise it is the current period.
+// only the matching transactions count as spent
+fn csv_summary(paths: &Paths, filter: Filter) -> Result<(), Box<dyn Error>> {
+    let today = Date::today();
+    let (period, as_of, mut transactions, limits) = match (filter.from, filter.to) {
+        (Some(from), Some(to)) => {
+            let range = Period::new(from, to).ok_or("--from must not be after --to")?;
+            let (transactions, limits) =
+                status::range_data(paths, &Archive::open(paths.archive()), range)?;
+            (
+                range,
+                today.min(range.end().next_day()),
+                transactions,
+                limits,
+            )
+        }
+        (None, None) => {
+            let storage = storage::open(paths);
+            (
+                settings::get().budget.period.period_of(today),
+                today,
+                storage.load()?.transactions,
+                storage.limits()?.limits,
+            )
+        }
+        _ => return Err("--summary needs both --from and --to, or neither".into()),
+    };
+    transactions.retain(|t| filter.matches(t));
+
+    let tree =
+        TransactionTree::from_transactions_and_limits(&transactions, &limits, period, as_of)?;
+
+    let mut out = io::stdout().lock();
+    csv::write_record(&mut out, &["tag", "spent", "limit", "remaining"])?;
+    // the root comes first, with an empty tag, and holds the totals
+    for (tag, spent, limit) in tree.summary() {
+        let (limit, remaining) = match limit {
+            Some(limit) => (
+                limit.to_plain_string(),
+                limit
+                    .checked_sub(spent)
+                    .ok_or("the remaining amount overflowed")?
+                    .to_plain_string(),
+            ),
+            None => (String::new(), String::new()),
+        };
+        csv::write_record(
+            &mut out,
+            &[
+                &tag.to_tag().to_dotted_string(),
+                &spent.to_plain_string(),
+                &limit,
+                &remaining,
+            ],
+        )?;
+    }
+    out.flush()?;
+
+    Ok(())
+}
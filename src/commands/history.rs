@@ -0,0 +1,84 @@
+use std::error::Error;
+
+use crate::journal::Journal;
+use crate::lock::DataLock;
+use crate::paths::Paths;
+use crate::storage::{self, Storage};
+use crate::transaction::{Transaction, TransactionId};
+
+pub fn undo(paths: &Paths) -> Result<(), Box<dyn Error>> {
+    let lock = DataLock::acquire(paths.lock())?;
+    let mut journal = Journal::from_json_file_or_empty(paths.journal())?;
+    let entry = journal.undo().ok_or("nothing to undo")?.clone();
+
+    let mut storage = storage::open_for_change(paths, &lock)?;
+    let mut transactions = storage.load()?;
+    let mut limits = storage.limits()?;
+    entry
+        .revert(&mut transactions, &mut limits)
+        .map_err(|e| format!("cannot undo \"{}\": {}", entry.description, e))?;
+
+    replace(storage.as_mut(), &entry.added, &entry.removed)?;
+    if entry.limits.is_some() {
+        storage.save_limits(&limits)?;
+    }
+    journal.to_json_file(paths.journal())?;
+
+    println!("undid {}", entry.description);
+
+    Ok(())
+}
+
+pub fn redo(paths: &Paths) -> Result<(), Box<dyn Error>> {
+    let lock = DataLock::acquire(paths.lock())?;
+    let mut journal = Journal::from_json_file_or_empty(paths.journal())?;
+    let entry = journal.redo().ok_or("nothing to redo")?.clone();
+
+    let mut storage = storage::open_for_change(paths, &lock)?;
+    let mut transactions = storage.load()?;
+    let mut limits = storage.limits()?;
+    entry
+        .apply(&mut transactions, &mut limits)
+        .map_err(|e| format!("cannot redo \"{}\": {}", entry.description, e))?;
+
+    replace(storage.as_mut(), &entry.removed, &entry.added)?;
+    if entry.limits.is_some() {
+        storage.save_limits(&limits)?;
+    }
+    journal.to_json_file(paths.journal())?;
+
+    println!("redid {}", entry.description);
+
+    Ok(())
+}
+
+pub fn log(paths: &Paths, count: usize) -> Result<(), Box<dyn Error>> {
+    let journal = Journal::from_json_file_or_empty(paths.journal())?;
+
+    for (number, entry, applied) in journal.recent(count) {
+        print!("{:>3}  {}  {}", number, entry.date, entry.description);
+        if !applied {
+            print!(" (undone)");
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+// the entry has already been checked against the loaded transactions, so
+// this only has to carry it out
+fn replace(
+    storage: &mut dyn Storage,
+    take_out: &[Transaction],
+    put_in: &[Transaction],
+) -> Result<(), Box<dyn Error>> {
+    if take_out.is_empty() && put_in.is_empty() {
+        return Ok(());
+    }
+
+    let ids: Vec<TransactionId> = take_out.iter().map(|t| t.id.clone()).collect();
+    storage.replace(&ids, put_in.to_vec())?;
+
+    Ok(())
+}
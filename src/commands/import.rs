@@ -0,0 +1,208 @@
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use crate::archive::Archive;
+use crate::import;
+use crate::journal::{self, Entry};
+use crate::lock::DataLock;
+use crate::money::Money;
+use crate::paths::Paths;
+use crate::settings;
+use crate::storage;
+use crate::transaction::{Transaction, TransactionId, Transactions};
+
+pub struct Options {
+    // only show what would be imported
+    pub dry_run: bool,
+    // import without asking first
+    pub yes: bool,
+}
+
+pub fn ledger(
+    paths: &Paths,
+    file: &Path,
+    prefix: Option<String>,
+    options: Options,
+) -> Result<(), Box<dyn Error>> {
+    let contents = fs::read_to_string(file)
+        .map_err(|e| format!("could not open {}: {}", file.display(), e))?;
+    let prefix = prefix.unwrap_or_else(|| settings::get().import.account_prefix.clone());
+
+    let imported = import::ledger::parse(&contents, &prefix)
+        .map_err(|e| format!("{}: {}", file.display(), e))?;
+    if imported.is_empty() {
+        return Err(format!(
+            "{} has no postings to accounts under {}",
+            file.display(),
+            prefix
+        )
+        .into());
+    }
+
+    return write(paths, file, imported, options);
+}
+
+// the bank's amounts are turned around, so that money leaving the account is
+// spent; money coming in is negative, as it is for refunds
+pub fn ofx(
+    paths: &Paths,
+    file: &Path,
+    skip_credits: bool,
+    options: Options,
+) -> Result<(), Box<dyn Error>> {
+    // statements are often in Latin-1 or Windows-1252 rather than UTF-8
+    let contents =
+        fs::read(file).map_err(|e| format!("could not open {}: {}", file.display(), e))?;
+    let contents = String::from_utf8(contents)
+        .unwrap_or_else(|e| e.into_bytes().iter().map(|b| char::from(*b)).collect());
+    let statement =
+        import::ofx::parse(&contents).map_err(|e| format!("{}: {}", file.display(), e))?;
+
+    let settings = &settings::get().import;
+    let mut rules = Vec::new();
+    for rule in &settings.rules {
+        rules.push((rule.regex()?, &rule.tag));
+    }
+
+    let mut imported = Vec::new();
+    for bank in statement {
+        if skip_credits && bank.amount > Money::from_cents(0) {
+            continue;
+        }
+
+        let description = bank.description();
+        let tag = rules
+            .iter()
+            .find(|(regex, _)| regex.is_match(&description))
+            .map_or(&settings.fallback_tag, |(_, tag)| *tag);
+
+        imported.push(Transaction {
+            date: bank.date,
+            amount: Money::from_cents(0)
+                .checked_sub(bank.amount)
+                .ok_or("an amount overflowed")?,
+            tag: tag.clone(),
+            comment: description,
+            id: TransactionId::default(),
+            bank_id: Some(bank.bank_id),
+        });
+    }
+    if imported.is_empty() {
+        return Err(format!("{} has no transactions to import", file.display()).into());
+    }
+
+    return write(paths, file, imported, options);
+}
+
+// shows every imported transaction, marking the ones the budget already has,
+// and adds the rest as one change that budget undo takes back. the question
+// is asked before the data lock is taken, so that no other command waits on
+// the answer, and what to add is worked out again under the lock
+fn write(
+    paths: &Paths,
+    file: &Path,
+    imported: Vec<Transaction>,
+    options: Options,
+) -> Result<(), Box<dyn Error>> {
+    let storage = storage::open(paths);
+    let transactions = storage.load()?;
+    let duplicates = find_duplicates(paths, &transactions, &imported)?;
+    for (t, duplicate) in imported.iter().zip(&duplicates) {
+        let marker = if *duplicate { "have" } else { "new " };
+        print!("{}  {}  {}  {}", marker, t.date, t.tag, t.amount);
+        if !t.comment.is_empty() {
+            print!("  {}", t.comment);
+        }
+        println!();
+    }
+
+    let mut new = without_duplicates(&imported, &duplicates);
+    if new.is_empty() {
+        println!("the budget already has all of them");
+        return Ok(());
+    }
+
+    import::check_tags(&transactions.transactions, &storage.limits()?, &new)?;
+
+    if options.dry_run {
+        println!("{} new transactions would be imported", new.len());
+        return Ok(());
+    }
+
+    if !options.yes && !confirm(&format!("import {} new transactions?", new.len()))? {
+        return Err("nothing was imported".into());
+    }
+
+    let lock = DataLock::acquire(paths.lock())?;
+    let mut storage = storage::open_for_change(paths, &lock)?;
+    let mut transactions = storage.load()?;
+    if find_duplicates(paths, &transactions, &imported)? != duplicates {
+        return Err(
+            "the budget changed while the import was waiting for an answer; run it again".into(),
+        );
+    }
+    import::check_tags(&transactions.transactions, &storage.limits()?, &new)?;
+
+    for t in &mut new {
+        if !t.id.is_assigned() || transactions.position(&t.id).is_some() {
+            t.id = transactions.new_id();
+        }
+        transactions.add(t.clone());
+    }
+
+    storage.append_all(new.clone())?;
+    journal::record(
+        paths.journal(),
+        Entry::transactions(
+            format!("import {} transactions from {}", new.len(), file.display()),
+            Vec::new(),
+            new.clone(),
+        ),
+    )?;
+
+    println!("imported {} transactions", new.len());
+
+    Ok(())
+}
+
+// which of the imported transactions the budget already has, among its
+// current transactions and the archived months the import covers
+fn find_duplicates(
+    paths: &Paths,
+    transactions: &Transactions,
+    imported: &[Transaction],
+) -> Result<Vec<bool>, Box<dyn Error>> {
+    let from = imported.iter().map(|t| t.date).min();
+    let to = imported.iter().map(|t| t.date).max();
+    let mut existing = transactions.transactions.clone();
+    existing.append(&mut Archive::open(paths.archive()).transactions_between(from, to)?);
+
+    return Ok(import::duplicates(&existing, imported));
+}
+
+fn without_duplicates(imported: &[Transaction], duplicates: &[bool]) -> Vec<Transaction> {
+    imported
+        .iter()
+        .zip(duplicates)
+        .filter(|(_, duplicate)| !**duplicate)
+        .map(|(t, _)| t.clone())
+        .collect()
+}
+
+fn confirm(question: &str) -> Result<bool, Box<dyn Error>> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(
+            "run it again with --yes to import them, or with --dry-run to only look".into(),
+        );
+    }
+
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+
+    return Ok(line.trim().eq_ignore_ascii_case("y"));
+}
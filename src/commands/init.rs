@@ -3,35 +3,55 @@ use std::error::Error;
 use std::{
     env::Args,
     fs,
-    io::{self, BufWriter},
-    path::Path,
+    io::{self, BufWriter, Write},
     time::Instant,
 };
 
+use crate::paths::Paths;
+use crate::settings::Settings;
+use crate::storage::{Format, jsonl};
 use crate::transaction::Transactions;
 
-pub fn init() -> Result<(), Box<dyn Error>> {
-    let archive_path = Path::new("archive");
-    let transactions_path = Path::new("transactions.json");
+pub fn init(paths: &Paths, format: Format) -> Result<(), Box<dyn Error>> {
+    let archive_path = &paths.archive();
+    let transactions_path = &format.path(paths);
+    let existing_path = &Format::of(paths).path(paths);
 
     let transactions = Transactions::empty();
 
-    match (archive_path.exists(), transactions_path.exists()) {
+    match (archive_path.exists(), existing_path.exists()) {
         (true, true) => {
-            return Err(
-                "archive/ and transactions.json already exist; maybe you already ran budget init?"
-                    .into(),
-            );
+            return Err(format!(
+                "archive/ and {} already exist; maybe you already ran budget init?",
+                existing_path.display()
+            )
+            .into());
         }
         (true, false) => return Err("archive/ already exists; move or delete it".into()),
-        (false, true) => return Err("transactions.json already exists; move or delete it".into()),
+        (false, true) => {
+            return Err(format!(
+                "{} already exists; move or delete it",
+                existing_path.display()
+            )
+            .into());
+        }
         (false, false) => (),
     };
 
-    let transactions_file_unbuffered = fs::File::create_new(transactions_path)?;
-    let transactions_file = BufWriter::new(transactions_file_unbuffered);
-    transactions.to_json_writer(transactions_file)?;
+    fs::create_dir_all(paths.root())?;
+    let mut transactions_file_unbuffered = fs::File::create_new(transactions_path)?;
+    // a log without transactions holds just its version
+    match format {
+        Format::Json => {
+            let transactions_file = BufWriter::new(transactions_file_unbuffered);
+            transactions.to_json_writer(transactions_file)?;
+        }
+        Format::Jsonl => transactions_file_unbuffered.write_all(&jsonl::header()?)?,
+    }
     fs::create_dir(archive_path)?;
+    if !paths.settings().exists() {
+        Settings::default().to_toml_file(paths.settings())?;
+    }
 
     Ok(())
 }
@@ -0,0 +1,156 @@
+use std::error::Error;
+
+use crate::date::Date;
+use crate::journal::{self, Entry};
+use crate::limits::Limits;
+use crate::lock::DataLock;
+use crate::money::Money;
+use crate::paths::Paths;
+use crate::settings;
+use crate::storage;
+use crate::tag::Tag;
+use crate::transaction_tree::TransactionTree;
+
+pub fn set(paths: &Paths, tag: Tag, amount: Money) -> Result<(), Box<dyn Error>> {
+    let lock = DataLock::acquire(paths.lock())?;
+    let mut limits = storage::open_for_change(paths, &lock)?.limits()?;
+    let before = limits.clone();
+    let previous = limits.limits.insert(tag.clone(), amount);
+    write_limits(
+        paths,
+        format!(
+            "limit set {} {}",
+            tag.to_dotted_string(),
+            amount.to_plain_string()
+        ),
+        before,
+        &limits,
+    )?;
+
+    match previous {
+        Some(previous) => println!("{}: {} (was {})", tag, amount, previous),
+        None => println!("{}: {}", tag, amount),
+    }
+
+    Ok(())
+}
+
+pub fn unset(paths: &Paths, tag: Tag) -> Result<(), Box<dyn Error>> {
+    let lock = DataLock::acquire(paths.lock())?;
+    let mut limits = storage::open_for_change(paths, &lock)?.limits()?;
+    let before = limits.clone();
+    let previous = limits
+        .limits
+        .remove(&tag)
+        .ok_or_else(|| format!("{} has no limit", tag))?;
+    write_limits(
+        paths,
+        format!("limit unset {}", tag.to_dotted_string()),
+        before,
+        &limits,
+    )?;
+
+    println!("{}: no limit (was {})", tag, previous);
+
+    Ok(())
+}
+
+pub fn list(paths: &Paths) -> Result<(), Box<dyn Error>> {
+    let limits = storage::open(paths).limits()?;
+
+    for (tag, amount) in &limits.limits {
+        match limits.children_total(tag)? {
+            Some(total) => println!("{}: {} (children: {})", tag, amount, total),
+            None => println!("{}: {}", tag, amount),
+        }
+    }
+
+    Ok(())
+}
+
+pub fn show(paths: &Paths, tag: Tag) -> Result<(), Box<dyn Error>> {
+    let limits = storage::open(paths).limits()?;
+
+    match limits.limits.get(&tag) {
+        Some(amount) => println!("{}: {}", tag, amount),
+        None => println!("{}: no limit", tag),
+    }
+
+    for (child, amount) in limits.children_of(&tag) {
+        println!("  {}: {}", child, amount);
+    }
+
+    if let Some(total) = limits.children_total(&tag)? {
+        println!("children: {}", total);
+    }
+
+    Ok(())
+}
+
+pub fn check(paths: &Paths) -> Result<(), Box<dyn Error>> {
+    let limits = storage::open(paths).limits()?;
+    let issues = limits.check()?;
+    for issue in &issues {
+        println!("{}", issue);
+    }
+
+    let problems = issues.iter().filter(|issue| issue.is_problem()).count();
+    match problems {
+        0 => println!("the limits are consistent"),
+        1 => return Err("found 1 inconsistent limit".into()),
+        _ => return Err(format!("found {} inconsistent limits", problems).into()),
+    }
+
+    Ok(())
+}
+
+pub fn derive(paths: &Paths) -> Result<(), Box<dyn Error>> {
+    let lock = DataLock::acquire(paths.lock())?;
+    let mut limits = storage::open_for_change(paths, &lock)?.limits()?;
+    let before = limits.clone();
+    let derived = limits.derive_parents()?;
+    if derived.is_empty() {
+        println!("every parent already has a limit");
+        return Ok(());
+    }
+
+    write_limits(paths, "limit derive".to_string(), before, &limits)?;
+
+    for (tag, amount) in derived {
+        println!("{}: {} (derived from its children)", tag, amount);
+    }
+
+    Ok(())
+}
+
+// the limits are checked against the current transactions and read back
+// before anything is written, so limits.json always holds limits that load
+fn write_limits(
+    paths: &Paths,
+    description: String,
+    before: Limits,
+    limits: &Limits,
+) -> Result<(), Box<dyn Error>> {
+    let transactions = storage::open(paths).load()?;
+    let today = Date::today();
+    TransactionTree::from_transactions_and_limits(
+        &transactions.transactions,
+        &limits.limits,
+        settings::get().budget.period.period_of(today),
+        today,
+    )?;
+
+    let mut json = Vec::new();
+    limits.to_json_writer(&mut json)?;
+    if Limits::from_json_reader(&json[..])? != *limits {
+        return Err("the limits changed when read back; they were not saved".into());
+    }
+
+    storage::open(paths).save_limits(limits)?;
+    journal::record(
+        paths.journal(),
+        Entry::limits(description, before, limits.clone()),
+    )?;
+
+    Ok(())
+}
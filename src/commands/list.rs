@@ -0,0 +1,65 @@
+use std::error::Error;
+
+use clap::ValueEnum;
+
+use crate::archive::Archive;
+use crate::filter::Filter;
+use crate::money::Money;
+use crate::paths::Paths;
+use crate::storage;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum SortKey {
+    Date,
+    Amount,
+    Tag,
+}
+
+pub fn list(
+    paths: &Paths,
+    filter: Filter,
+    sort: SortKey,
+    reverse: bool,
+    limit: Option<usize>,
+    offset: usize,
+) -> Result<(), Box<dyn Error>> {
+    let mut transactions = storage::open(paths).range(filter.from, filter.to)?;
+    // ids are only unique within a file, so --id picks among the current
+    // transactions, the same ones remove and edit pick from
+    if filter.id.is_none() {
+        let archive = Archive::open(paths.archive());
+        transactions.append(&mut archive.transactions_between(filter.from, filter.to)?);
+    }
+
+    transactions.retain(|t| filter.matches(t));
+
+    // sorting is stable and transactions sort by date first, so ties are
+    // always broken by date
+    transactions.sort();
+    match sort {
+        SortKey::Date => {}
+        SortKey::Amount => transactions.sort_by_key(|t| t.amount),
+        SortKey::Tag => transactions.sort_by(|a, b| a.tag.cmp(&b.tag)),
+    }
+
+    if reverse {
+        transactions.reverse();
+    }
+
+    let shown = transactions
+        .iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX));
+
+    let mut total = Money::from_cents(0);
+    for t in shown {
+        total = total.checked_add(t.amount).ok_or("the total overflowed")?;
+        print!("{}  {}  {}  {}  {}", t.id, t.date, t.tag, t.amount, total);
+        if !t.comment.is_empty() {
+            print!("  {}", t.comment);
+        }
+        println!();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::json;
+
+use crate::archive::Archive;
+use crate::limits::Limits;
+use crate::lock::DataLock;
+use crate::paths::Paths;
+use crate::schema::{self, Schema};
+use crate::storage::jsonl;
+use crate::transaction::Transactions;
+
+// every versioned file of the budget that exists, current and archived
+fn data_files(paths: &Paths) -> Result<Vec<(PathBuf, &'static Schema)>, Box<dyn Error>> {
+    let mut files = vec![
+        (paths.transactions(), &schema::TRANSACTIONS),
+        (paths.transactions_log(), &schema::TRANSACTIONS_LOG),
+        (paths.limits(), &schema::LIMITS),
+    ];
+
+    let archive = Archive::open(paths.archive());
+    for month in archive.months()? {
+        files.push((archive.transactions_path(month), &schema::TRANSACTIONS));
+        files.push((archive.limits_path(month), &schema::LIMITS));
+    }
+    files.retain(|(path, _)| path.exists());
+
+    return Ok(files);
+}
+
+fn pending(paths: &Paths) -> Result<Vec<(PathBuf, &'static Schema)>, Box<dyn Error>> {
+    let mut pending = Vec::new();
+    for (path, schema) in data_files(paths)? {
+        // the log is not one JSON value, so only its version is looked at
+        let value = if schema.name == schema::TRANSACTIONS_LOG.name {
+            let contents = fs::read_to_string(&path)?;
+            json!({ "version": jsonl::version(&contents).map_err(|e| format!("{}: {}", path.display(), e))? })
+        } else {
+            schema::read(&path)?
+        };
+        let migrations = schema
+            .pending(&value)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        if migrations.is_empty() {
+            continue;
+        }
+
+        println!("{}:", path.display());
+        for migration in migrations {
+            println!("  version {}: {}", migration.to, migration.description);
+        }
+        pending.push((path, schema));
+    }
+
+    return Ok(pending);
+}
+
+// a change upgrades the current files it touches; this does all of them at
+// once, archived months included
+pub fn migrate(paths: &Paths, check: bool) -> Result<(), Box<dyn Error>> {
+    let _lock = DataLock::acquire(paths.lock())?;
+    let pending = pending(paths)?;
+    if pending.is_empty() {
+        println!("every data file is up to date");
+        return Ok(());
+    }
+
+    if check {
+        return Err(format!(
+            "{} data files need upgrading; run budget migrate",
+            pending.len()
+        )
+        .into());
+    }
+
+    for (path, schema) in &pending {
+        if schema.name == schema::TRANSACTIONS.name {
+            Transactions::upgrade_json_file(path)?;
+        } else if schema.name == schema::TRANSACTIONS_LOG.name {
+            jsonl::upgrade_log(path)?;
+        } else {
+            Limits::upgrade_json_file(path)?;
+        }
+    }
+    println!("upgraded {} data files", pending.len());
+
+    Ok(())
+}
@@ -1,8 +1,21 @@
 use std::{error::Error, fmt};
 
 pub mod add;
+pub mod config;
+pub mod edit;
+pub mod export;
+pub mod history;
+pub mod import;
 pub mod init;
+pub mod limit;
+pub mod list;
+pub mod migrate;
+pub mod profile;
+pub mod query;
 pub mod remove;
+pub mod select;
+pub mod status;
+pub mod storage;
 
 #[derive(Debug)]
 struct CommandError {
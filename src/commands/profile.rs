@@ -0,0 +1,70 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use crate::commands::init::init;
+use crate::paths::Paths;
+use crate::profiles::{self, Profiles};
+use crate::storage::Format;
+
+pub fn list() -> Result<(), Box<dyn Error>> {
+    let profiles = Profiles::load()?;
+    if profiles.profiles.is_empty() {
+        println!("there are no profiles yet; create one with budget profile create");
+        return Ok(());
+    }
+
+    for (name, dir) in &profiles.profiles {
+        let marker = if profiles.default.as_ref() == Some(name) {
+            "*"
+        } else {
+            " "
+        };
+        println!("{} {}  {}", marker, name, dir.display());
+    }
+
+    Ok(())
+}
+
+// an existing budget directory is adopted as it is; otherwise a new budget
+// is set up there. the first profile becomes the default
+pub fn create(name: String, dir: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    profiles::validate_name(&name)?;
+
+    let mut profiles = Profiles::load()?;
+    if profiles.profiles.contains_key(&name) {
+        return Err(format!("there already is a profile named {}", name).into());
+    }
+
+    let dir = match dir {
+        Some(dir) => dir,
+        None => Profiles::default_directory(&name)?,
+    };
+    let dir = std::path::absolute(dir)?;
+
+    let paths = Paths::new(&dir);
+    if !paths.transactions().exists() && !paths.transactions_log().exists() {
+        init(&paths, Format::Json)?;
+    }
+
+    println!("created profile {} in {}", name, dir.display());
+    profiles.profiles.insert(name.clone(), dir);
+    if profiles.default.is_none() {
+        println!("{} is now the default profile", name);
+        profiles.default = Some(name);
+    }
+    profiles.save()?;
+
+    Ok(())
+}
+
+pub fn switch(name: String) -> Result<(), Box<dyn Error>> {
+    let mut profiles = Profiles::load()?;
+    profiles.paths(&name)?;
+
+    profiles.default = Some(name.clone());
+    profiles.save()?;
+
+    println!("{} is now the default profile", name);
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use std::error::Error;
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::archive::Archive;
+use crate::money::Money;
+use crate::paths::Paths;
+use crate::query::{Aggregate, Query, Row};
+use crate::storage;
+use crate::transaction::Transaction;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Format {
+    Table,
+    Json,
+}
+
+// only the aggregates that were asked for are included
+#[derive(Serialize)]
+struct JsonRow<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sum: Option<Money>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    avg: Option<Option<Money>>,
+}
+
+pub fn query(paths: &Paths, query: &str, format: Format) -> Result<(), Box<dyn Error>> {
+    let mut query = Query::from_str(query)?;
+
+    let mut transactions = storage::open(paths).load()?.transactions;
+    let archive = Archive::open(paths.archive());
+    transactions.append(&mut archive.transactions_between(None, None)?);
+    transactions.sort();
+
+    if !query.is_aggregated() {
+        let matching: Vec<&Transaction> =
+            transactions.iter().filter(|t| query.matches(t)).collect();
+        return print_transactions(&matching, format);
+    }
+
+    // grouping without naming any aggregates shows the totals
+    if query.aggregates.is_empty() {
+        query.aggregates = vec![Aggregate::Sum, Aggregate::Count];
+    }
+
+    let rows = query
+        .rows(&transactions)
+        .ok_or("adding up the transactions overflowed")?;
+
+    return print_rows(&rows, &query.aggregates, format);
+}
+
+fn print_transactions(transactions: &[&Transaction], format: Format) -> Result<(), Box<dyn Error>> {
+    match format {
+        Format::Json => println!("{}", serde_json::to_string_pretty(transactions)?),
+        Format::Table => {
+            for t in transactions {
+                print!("{}  {}  {}", t.date, t.tag, t.amount);
+                if !t.comment.is_empty() {
+                    print!("  {}", t.comment);
+                }
+                println!();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_rows(
+    rows: &[Row],
+    aggregates: &[Aggregate],
+    format: Format,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        Format::Json => {
+            let rows: Vec<JsonRow> = rows
+                .iter()
+                .map(|row| JsonRow {
+                    group: row.group.as_deref(),
+                    sum: aggregates.contains(&Aggregate::Sum).then_some(row.sum),
+                    count: aggregates.contains(&Aggregate::Count).then_some(row.count),
+                    avg: aggregates.contains(&Aggregate::Avg).then(|| row.avg()),
+                })
+                .collect();
+
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+        }
+        Format::Table => {
+            for row in rows {
+                let mut columns = Vec::new();
+                if let Some(group) = &row.group {
+                    columns.push(group.clone());
+                }
+
+                for aggregate in aggregates {
+                    columns.push(match aggregate {
+                        Aggregate::Sum => format!("sum {}", row.sum),
+                        Aggregate::Count => format!("count {}", row.count),
+                        Aggregate::Avg => match row.avg() {
+                            Some(avg) => format!("avg {}", avg),
+                            None => "avg -".to_string(),
+                        },
+                    });
+                }
+
+                println!("{}", columns.join("  "));
+            }
+        }
+    }
+
+    Ok(())
+}
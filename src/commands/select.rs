@@ -0,0 +1,135 @@
+use std::error::Error;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use crate::paths::Paths;
+use crate::storage::Format;
+use crate::transaction::{Selector, Transaction, Transactions};
+
+// finds the index of the single transaction the selector picks out; see
+// `select` for what happens when several match
+pub fn select_one(
+    transactions: &Transactions,
+    selector: &Selector,
+    verb: &str,
+    fallback: &str,
+) -> Result<usize, Box<dyn Error>> {
+    let selected = select(transactions, selector, verb, fallback, false)?;
+
+    return Ok(selected[0]);
+}
+
+// finds the indices of the transactions the selector picks out. when several
+// match and a terminal is attached, the user picks one of them (or all of
+// them, when `allow_all` is set); otherwise they are printed and the error
+// suggests what else to narrow the match with, with `fallback` as the
+// suggestion once there is nothing left to add
+pub fn select(
+    transactions: &Transactions,
+    selector: &Selector,
+    verb: &str,
+    fallback: &str,
+    allow_all: bool,
+) -> Result<Vec<usize>, Box<dyn Error>> {
+    let matching = transactions.matching(selector);
+
+    if let (0, Some(id)) = (matching.len(), &selector.id) {
+        return Err(format!("no transaction with id {} found", id).into());
+    }
+
+    match matching.len() {
+        0 => return Err("no such transaction found".into()),
+        1 => return Ok(matching),
+        _ => {}
+    }
+
+    println!("multiple matching transactions found:");
+    for (number, index) in matching.iter().enumerate() {
+        let t = &transactions.transactions[*index];
+        println!("  {}) {}  {}: {}", number + 1, t.id, t.tag, t);
+    }
+
+    if io::stdin().is_terminal() && io::stdout().is_terminal() {
+        return prompt(&matching, verb, allow_all);
+    }
+
+    let suggestion = match (&selector.date, &selector.comment) {
+        (None, None) => "try including the transaction's date or comment, or its --id".to_string(),
+        (Some(_), None) => "try including the transaction's comment, or its --id".to_string(),
+        (None, Some(_)) => "try including the transaction's date, or its --id".to_string(),
+        (Some(_), Some(_)) => format!("try selecting it with --id, or {}", fallback),
+    };
+
+    return Err(format!("multiple matching transactions found; {}", suggestion).into());
+}
+
+// the last resort for telling transactions apart, as the fallback for
+// `select`. transactions.jsonl is a log that is not for editing by hand, so
+// a budget that uses it has to be converted first
+pub fn by_hand(paths: &Paths, action: &str) -> String {
+    match Format::of(paths) {
+        Format::Json => format!("manually {} {}", action, paths.transactions().display()),
+        Format::Jsonl => format!(
+            "run budget storage convert json, then manually {} {}",
+            action,
+            paths.transactions().display()
+        ),
+    }
+}
+
+// the transactions are picked before the data lock is taken, so that no other
+// command waits on the user. once it is taken, the picked ones must still be
+// there as they were
+pub fn check_unchanged(
+    transactions: &Transactions,
+    picked: &[Transaction],
+) -> Result<(), Box<dyn Error>> {
+    for t in picked {
+        let current = transactions
+            .position(&t.id)
+            .map(|index| &transactions.transactions[index]);
+        if current != Some(t) {
+            return Err(format!(
+                "transaction {} changed while it was being picked; run the command again",
+                t.id
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+fn prompt(matching: &[usize], verb: &str, allow_all: bool) -> Result<Vec<usize>, Box<dyn Error>> {
+    let choices = if allow_all {
+        format!("1-{}, a for all", matching.len())
+    } else {
+        format!("1-{}", matching.len())
+    };
+
+    let mut stdin = io::stdin().lock();
+    loop {
+        print!("{} which? [{}, empty to cancel] ", verb, choices);
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line)? == 0 {
+            return Err("nothing was selected".into());
+        }
+
+        let answer = line.trim();
+        if answer.is_empty() {
+            return Err("nothing was selected".into());
+        }
+
+        if allow_all && answer.eq_ignore_ascii_case("a") {
+            return Ok(matching.to_vec());
+        }
+
+        match answer.parse::<usize>() {
+            Ok(number) if (1..=matching.len()).contains(&number) => {
+                return Ok(vec![matching[number - 1]]);
+            }
+            _ => println!("expected {}", choices),
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+
+use crate::archive::Archive;
+use crate::date::{Date, Month};
+use crate::forecast::Forecast;
+use crate::money::Money;
+use crate::paths::Paths;
+use crate::period::Period;
+use crate::profiles::Profiles;
+use crate::settings::{self, Settings};
+use crate::storage;
+use crate::tag::Tag;
+use crate::transaction::Transaction;
+use crate::transaction_tree::TransactionTree;
+
+// how many archived months feed the historical pace of the forecast
+const FORECAST_HISTORY_MONTHS: usize = 3;
+
+pub fn status(
+    paths: &Paths,
+    month: Option<Month>,
+    range: Option<Period>,
+) -> Result<(), Box<dyn Error>> {
+    let today = Date::today();
+    let archive = Archive::open(paths.archive());
+
+    if let Some(range) = range {
+        return range_status(paths, &archive, range, today);
+    }
+
+    match month {
+        Some(month) if archive.contains(month) => archived_status(&archive, month),
+        Some(month) if month != Month::of(today) => {
+            Err(format!("{} has not been archived", month).into())
+        }
+        _ => current_status(paths, &archive, today),
+    }
+}
+
+// each profile's status in turn, under its name
+pub fn all_profiles(month: Option<Month>, range: Option<Period>) -> Result<(), Box<dyn Error>> {
+    let profiles = Profiles::load()?;
+    if profiles.profiles.is_empty() {
+        return Err("there are no profiles; create one with budget profile create".into());
+    }
+
+    for (i, name) in profiles.profiles.keys().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{}", name);
+
+        let paths = profiles.paths(name)?;
+        Settings::from_toml_file_or_default(paths.settings())
+            .map(settings::install)
+            .and_then(|()| status(&paths, month, range))
+            .map_err(|e| format!("profile {}: {}", name, e))?;
+    }
+
+    Ok(())
+}
+
+// an archived month is shown as it stood once its last day was over, so
+// nothing is left to spend and everything has accrued
+fn archived_status(archive: &Archive, month: Month) -> Result<(), Box<dyn Error>> {
+    let transactions = archive.transactions(month)?;
+    let limits = archive.limits(month)?;
+
+    let tree = TransactionTree::from_transactions_and_limits(
+        &transactions.transactions,
+        &limits.limits,
+        Period::month_of(month.first_day()),
+        month.last_day().next_day(),
+    )?;
+
+    print!("{}", tree);
+
+    Ok(())
+}
+
+// a range can span archived months and the current one; each month
+// contributes its own limits, scaled by how much of the month is in the range
+fn range_status(
+    paths: &Paths,
+    archive: &Archive,
+    range: Period,
+    today: Date,
+) -> Result<(), Box<dyn Error>> {
+    let (transactions, limits) = range_data(paths, archive, range)?;
+
+    // a range that is already over is shown as of its end
+    let as_of = today.min(range.end().next_day());
+    let tree = TransactionTree::from_transactions_and_limits(&transactions, &limits, range, as_of)?;
+
+    println!("{}", range);
+    print!("{}", tree);
+
+    Ok(())
+}
+
+// the transactions in the range, sorted, and the limits that cover it
+pub type RangeData = (Vec<Transaction>, BTreeMap<Tag, Money>);
+
+pub fn range_data(
+    paths: &Paths,
+    archive: &Archive,
+    range: Period,
+) -> Result<RangeData, Box<dyn Error>> {
+    let storage = storage::open(paths);
+    let current_limits = storage.limits()?;
+    let mut transactions = storage.range(Some(range.start()), Some(range.end()))?;
+
+    let mut limits: BTreeMap<Tag, Money> = BTreeMap::new();
+    for month in range.months() {
+        let month_period = Period::month_of(month.first_day());
+        let covered = range
+            .intersection(month_period)
+            .ok_or("the range does not cover one of its own months")?;
+
+        let month_limits = if archive.contains(month) {
+            let mut archived = archive.transactions(month)?.transactions;
+            archived.retain(|t| range.contains(t.date));
+            transactions.append(&mut archived);
+
+            archive.limits(month)?
+        } else {
+            current_limits.clone()
+        };
+
+        for (tag, limit) in month_limits.limits {
+            let share = limit
+                .checked_scale(covered.days().into(), month_period.days().into())
+                .ok_or("scaling the limits overflowed")?;
+            let total = limits.entry(tag).or_insert(Money::from_cents(0));
+            *total = total
+                .checked_add(share)
+                .ok_or("adding up the limits overflowed")?;
+        }
+    }
+
+    transactions.sort();
+
+    return Ok((transactions, limits));
+}
+
+fn current_status(paths: &Paths, archive: &Archive, today: Date) -> Result<(), Box<dyn Error>> {
+    let storage = storage::open(paths);
+    let transactions = storage.load()?;
+    let limits = storage.limits()?;
+
+    let period = settings::get().budget.period.period_of(today);
+
+    let mut history = Vec::new();
+    for month in archive.months_before(Month::of(today), FORECAST_HISTORY_MONTHS)? {
+        history.push((month, archive.transactions(month)?));
+    }
+
+    let mut tree = TransactionTree::from_transactions_and_limits(
+        &transactions.transactions,
+        &limits.limits,
+        period,
+        today,
+    )?;
+    tree.set_forecast(Forecast::new(
+        period,
+        today,
+        &transactions.transactions,
+        &history,
+    ));
+
+    print!("{}", tree);
+
+    Ok(())
+}
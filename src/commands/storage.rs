@@ -0,0 +1,114 @@
+use std::error::Error;
+use std::fs;
+
+use crate::atomic_file;
+use crate::lock::DataLock;
+use crate::paths::Paths;
+use crate::storage::{self, Format, JsonlStorage};
+
+// the old file is kept as a backup next to the new one, so that a budget
+// never holds both formats at once. it is upgraded first, like any file that
+// is about to change, so that every transaction has an id of its own; the
+// log would keep only the last of several with the same id
+pub fn convert(paths: &Paths, to: Format) -> Result<(), Box<dyn Error>> {
+    let lock = DataLock::acquire(paths.lock())?;
+    let from = Format::of(paths);
+    if from == to {
+        return Err(format!(
+            "the transactions are already in {}",
+            to.path(paths).display()
+        )
+        .into());
+    }
+
+    let transactions = storage::open_for_change(paths, &lock)?.load()?;
+    to.write(paths, &transactions)?;
+
+    let old = from.path(paths);
+    let backup = atomic_file::backup_path(&old);
+    fs::rename(&old, &backup)?;
+
+    println!(
+        "moved {} transactions to {}; the old file is now {}",
+        transactions.transactions.len(),
+        to.path(paths).display(),
+        backup.display()
+    );
+
+    Ok(())
+}
+
+pub fn compact(paths: &Paths) -> Result<(), Box<dyn Error>> {
+    let _lock = DataLock::acquire(paths.lock())?;
+    if Format::of(paths) != Format::Jsonl {
+        return Err("only transactions.jsonl needs compacting".into());
+    }
+
+    let dropped = JsonlStorage::new(paths).compact()?;
+    println!("dropped {} superseded records", dropped);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scratch::ScratchDir;
+    use crate::transaction::{Transaction, Transactions};
+
+    #[test]
+    fn test_convert_round_trip() {
+        let directory = ScratchDir::new("convert");
+        let paths = Paths::new(directory.path());
+
+        let transactions = Transactions {
+            transactions: vec![Transaction {
+                bank_id: Some("9876:1".to_string()),
+                ..Transaction::sample("2025-09-12", -450, "food.coffee", "refund, \"sort of\"")
+                    .with_id("0000000a")
+            }],
+        };
+        transactions.to_json_file(paths.transactions()).unwrap();
+
+        convert(&paths, Format::Jsonl).unwrap();
+        assert_eq!(Format::of(&paths), Format::Jsonl);
+        assert!(!paths.transactions().exists());
+        assert!(convert(&paths, Format::Jsonl).is_err());
+
+        fs::remove_file(atomic_file::backup_path(&paths.transactions())).unwrap();
+        convert(&paths, Format::Json).unwrap();
+        assert_eq!(Format::of(&paths), Format::Json);
+        assert_eq!(
+            storage::open(&paths).load().unwrap().transactions,
+            transactions.transactions
+        );
+    }
+
+    #[test]
+    fn test_convert_keeps_transactions_without_ids_of_their_own() {
+        let directory = ScratchDir::new("convert-ids");
+        let paths = Paths::new(directory.path());
+        fs::write(
+            paths.transactions(),
+            r#"{"transactions": [
+                {"date": "2025-09-01", "amount": "4.50", "tag": "coffee", "comment": ""},
+                {"date": "2025-09-02", "amount": "3.00", "tag": "tea", "comment": "", "id": "0000000a"},
+                {"date": "2025-09-03", "amount": "9.00", "tag": "lunch", "comment": "", "id": "0000000a"}
+            ]}"#,
+        )
+        .unwrap();
+
+        convert(&paths, Format::Jsonl).unwrap();
+        let loaded = storage::open(&paths).load().unwrap();
+        let tags: Vec<String> = loaded
+            .transactions
+            .iter()
+            .map(|t| t.tag.to_dotted_string())
+            .collect();
+        assert_eq!(tags, vec!["coffee", "tea", "lunch"]);
+
+        // the old file was upgraded before it was moved aside
+        let moved = atomic_file::backup_path(&paths.transactions());
+        assert!(fs::read_to_string(moved).unwrap().contains("\"version\""));
+    }
+}
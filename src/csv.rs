@@ -0,0 +1,43 @@
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+// writes one record the way RFC 4180 has it: fields are separated by commas
+// and quoted when they hold a comma, a quote or a line break, with quotes
+// inside doubled. records end with CRLF
+pub fn write_record<W>(w: &mut W, fields: &[&str]) -> io::Result<()>
+where
+    W: Write,
+{
+    let fields: Vec<Cow<str>> = fields.iter().map(|field| quote(field)).collect();
+    write!(w, "{}\r\n", fields.join(","))
+}
+
+fn quote(field: &str) -> Cow<'_, str> {
+    if !field.contains([',', '"', '\r', '\n']) {
+        return Cow::Borrowed(field);
+    }
+
+    return Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoting() {
+        let mut out = Vec::new();
+        write_record(&mut out, &["2025-09-12", "food", "4.50", ""]).unwrap();
+        write_record(
+            &mut out,
+            &["lunch, with Sam", "the \"usual\"", "two\nlines"],
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "2025-09-12,food,4.50,\r\n\
+             \"lunch, with Sam\",\"the \"\"usual\"\"\",\"two\nlines\"\r\n"
+        );
+    }
+}
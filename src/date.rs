@@ -1,5 +1,5 @@
 use crate::number_parsers;
-use chrono::{Datelike, Local};
+use chrono::{Datelike, Days, Local, NaiveDate, Weekday};
 use serde::Serializer;
 use serde::{Deserialize, Deserializer, Serialize, de::Error};
 use std::fmt::{self, Display};
@@ -13,6 +13,13 @@ pub struct Date {
     day: u8,
 }
 
+// a calendar month, used to name archived months
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Copy)]
+pub struct Month {
+    year: u16,
+    month: u8,
+}
+
 #[derive(Error, Debug)]
 pub enum DateError {
     #[error("date should have exactly 3 components")]
@@ -53,6 +60,29 @@ impl FromStr for Date {
     }
 }
 
+impl FromStr for Month {
+    type Err = DateError;
+
+    fn from_str(s: &str) -> Result<Month, DateError> {
+        let mut segments = s.split('-');
+        let year_portion = segments.next().ok_or(DateError::InvalidSeparator)?;
+        let month_portion = segments.next().ok_or(DateError::InvalidSeparator)?;
+        if segments.next().is_some() {
+            return Err(DateError::InvalidSeparator);
+        }
+
+        let year: u16 =
+            number_parsers::unfixed_width(year_portion).ok_or(DateError::InvalidYear)?;
+        let month: u8 =
+            number_parsers::fixed_width(month_portion, 2).ok_or(DateError::InvalidMonth)?;
+        if month == 0 || month > 12 {
+            return Err(DateError::InvalidMonth);
+        }
+
+        return Ok(Month { year, month });
+    }
+}
+
 impl<'de> Deserialize<'de> for Date {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -99,6 +129,95 @@ impl Date {
         self.day
     }
 
+    // the given day in the same month, moved back to the last day of the month
+    // if the month is too short
+    pub fn with_day(&self, day: u8) -> Date {
+        Date {
+            day: day.clamp(1, self.days_in_month()),
+            ..*self
+        }
+    }
+
+    pub fn next_day(&self) -> Date {
+        if self.day < self.days_in_month() {
+            return Date {
+                day: self.day + 1,
+                ..*self
+            };
+        }
+
+        if self.month < 12 {
+            return Date {
+                month: self.month + 1,
+                day: 1,
+                ..*self
+            };
+        }
+
+        return Date {
+            year: self.year + 1,
+            month: 1,
+            day: 1,
+        };
+    }
+
+    pub fn first_of_month(&self) -> Date {
+        Date { day: 1, ..*self }
+    }
+
+    pub fn last_of_month(&self) -> Date {
+        Date {
+            day: self.days_in_month(),
+            ..*self
+        }
+    }
+
+    // the number of days from self to other, negative if other is earlier
+    pub fn days_until(&self, other: Date) -> i64 {
+        other
+            .to_naive()
+            .signed_duration_since(self.to_naive())
+            .num_days()
+    }
+
+    // the ISO 8601 week, e.g. 2025-W37, whose year can differ from the date's
+    // around the new year
+    pub fn iso_week(&self) -> String {
+        let week = self.to_naive().iso_week();
+        format!("{}-W{:02}", week.year(), week.week())
+    }
+
+    // the monday and sunday of the date's week
+    pub fn week_bounds(&self) -> (Date, Date) {
+        let week = self.to_naive().week(Weekday::Mon);
+
+        return (
+            Date::from_naive(week.first_day()),
+            Date::from_naive(week.last_day()),
+        );
+    }
+
+    // counted on from the first of the month, so a day the month does not
+    // have, like 02-31, runs into the next month rather than failing
+    fn to_naive(self) -> NaiveDate {
+        let date = NaiveDate::from_ymd_opt(self.year.into(), self.month.into(), 1)
+            .and_then(|first| first.checked_add_days(Days::new(u64::from(self.day.max(1)) - 1)));
+        match date {
+            Some(date) => date,
+            None => unreachable!("a date's year and month are always in chrono's range"),
+        }
+    }
+
+    // only called with dates a week away from a valid date, which stay in
+    // range unless the year is 0 or 65535
+    fn from_naive(date: NaiveDate) -> Date {
+        Date {
+            year: date.year().clamp(0, u16::MAX.into()) as u16,
+            month: date.month() as u8,
+            day: date.day() as u8,
+        }
+    }
+
     fn is_leap_year(&self) -> bool {
         if self.year % 400 == 0 {
             return true;
@@ -160,8 +279,98 @@ impl Date {
     }
 }
 
+impl Month {
+    pub fn of(date: Date) -> Month {
+        Month {
+            year: date.year,
+            month: date.month,
+        }
+    }
+
+    pub fn first_day(&self) -> Date {
+        Date {
+            year: self.year,
+            month: self.month,
+            day: 1,
+        }
+    }
+
+    pub fn last_day(&self) -> Date {
+        self.first_day().last_of_month()
+    }
+
+    pub fn next(&self) -> Month {
+        if self.month == 12 {
+            return Month {
+                year: self.year + 1,
+                month: 1,
+            };
+        }
+
+        return Month {
+            year: self.year,
+            month: self.month + 1,
+        };
+    }
+}
+
+impl Display for Month {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{:02}", self.year, self.month)
+    }
+}
+
 impl Display for Date {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}-{:02}-{:02}", self.year, self.month, self.day)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_until() {
+        let start = Date::from_str("2025-02-27").unwrap();
+        assert_eq!(start.days_until(Date::from_str("2025-03-01").unwrap()), 2);
+        assert_eq!(
+            start.days_until(Date::from_str("2024-02-27").unwrap()),
+            -366
+        );
+        assert_eq!(start.days_until(start), 0);
+    }
+
+    #[test]
+    fn test_iso_week() {
+        let week = |s| Date::from_str(s).unwrap().iso_week();
+        assert_eq!(week("2025-09-12"), "2025-W37");
+        assert_eq!(week("2024-12-30"), "2025-W01");
+        assert_eq!(week("2021-01-03"), "2020-W53");
+    }
+
+    #[test]
+    fn test_next_day() {
+        let next = |s| Date::from_str(s).unwrap().next_day().to_string();
+        assert_eq!(next("2024-02-28"), "2024-02-29");
+        assert_eq!(next("2025-02-28"), "2025-03-01");
+        assert_eq!(next("2025-12-31"), "2026-01-01");
+    }
+
+    #[test]
+    fn test_month() {
+        let month = Month::from_str("2025-01").unwrap();
+        assert_eq!(Month::from_str("2024-12").unwrap().next(), month);
+        assert_eq!(month.last_day(), Date::from_str("2025-01-31").unwrap());
+        assert_eq!(month.to_string(), "2025-01");
+        assert!(Month::from_str("2025-00").is_err());
+        assert!(Month::from_str("2025-1").is_err());
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        let date = Date::from_str("2024-02-12").unwrap();
+        assert_eq!(date.first_of_month(), Date::from_str("2024-02-01").unwrap());
+        assert_eq!(date.last_of_month(), Date::from_str("2024-02-29").unwrap());
+    }
+}
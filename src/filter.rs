@@ -0,0 +1,126 @@
+use crate::date::Date;
+use crate::money::Money;
+use crate::tag::Tag;
+use crate::transaction::{Transaction, TransactionId};
+use regex::Regex;
+
+// every condition that is set must hold for a transaction to match
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub tag: Option<Tag>,
+    // when set, the tag's descendants match as well as the tag itself
+    pub subtree: bool,
+    pub from: Option<Date>,
+    pub to: Option<Date>,
+    pub min: Option<Money>,
+    pub max: Option<Money>,
+    pub comment: Option<CommentMatch>,
+    pub id: Option<TransactionId>,
+}
+
+#[derive(Debug)]
+pub enum CommentMatch {
+    Contains(String),
+    Regex(Regex),
+}
+
+impl CommentMatch {
+    pub fn matches(&self, comment: &str) -> bool {
+        match self {
+            CommentMatch::Contains(s) => comment.contains(s.as_str()),
+            CommentMatch::Regex(r) => r.is_match(comment),
+        }
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, t: &Transaction) -> bool {
+        if let Some(tag) = &self.tag {
+            let is_descendant = self.subtree && tag.as_slice().is_ancestor_of(t.tag());
+            if t.tag != *tag && !is_descendant {
+                return false;
+            }
+        }
+
+        if self.from.is_some_and(|from| t.date < from) {
+            return false;
+        }
+
+        if self.to.is_some_and(|to| t.date > to) {
+            return false;
+        }
+
+        if self.min.is_some_and(|min| t.amount < min) {
+            return false;
+        }
+
+        if self.max.is_some_and(|max| t.amount > max) {
+            return false;
+        }
+
+        if self.id.as_ref().is_some_and(|id| *id != t.id) {
+            return false;
+        }
+
+        if let Some(comment) = &self.comment {
+            return comment.matches(&t.comment);
+        }
+
+        return true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn coffee() -> Transaction {
+        Transaction::sample("2025-09-12", 450, "food.coffee", "Blue Bottle")
+    }
+
+    #[test]
+    fn test_tag_and_subtree() {
+        let mut filter = Filter {
+            tag: Some(Tag::from_str("food").unwrap()),
+            ..Filter::default()
+        };
+        assert!(!filter.matches(&coffee()));
+
+        filter.subtree = true;
+        assert!(filter.matches(&coffee()));
+    }
+
+    #[test]
+    fn test_ranges_are_inclusive() {
+        let filter = Filter {
+            from: Some(Date::from_str("2025-09-12").unwrap()),
+            to: Some(Date::from_str("2025-09-12").unwrap()),
+            min: Some(Money::from_cents(450)),
+            max: Some(Money::from_cents(450)),
+            ..Filter::default()
+        };
+        assert!(filter.matches(&coffee()));
+
+        let filter = Filter {
+            min: Some(Money::from_cents(451)),
+            ..Filter::default()
+        };
+        assert!(!filter.matches(&coffee()));
+    }
+
+    #[test]
+    fn test_comment() {
+        let contains = Filter {
+            comment: Some(CommentMatch::Contains("Bottle".to_string())),
+            ..Filter::default()
+        };
+        assert!(contains.matches(&coffee()));
+
+        let regex = Filter {
+            comment: Some(CommentMatch::Regex(Regex::new("(?i)^blue").unwrap())),
+            ..Filter::default()
+        };
+        assert!(regex.matches(&coffee()));
+    }
+}
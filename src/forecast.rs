@@ -0,0 +1,268 @@
+use crate::date::{Date, Month};
+use crate::money::Money;
+use crate::period::Period;
+use crate::tag::{Tag, TagSlice};
+use crate::transaction::{Transaction, Transactions};
+use std::collections::{HashMap, HashSet};
+
+// projects where each tag will end the period: what has been spent so far,
+// plus recurring items that have not come due yet, plus the remaining days at
+// a rate that blends the current pace with the archived months' pace
+#[derive(Debug)]
+pub struct Forecast {
+    projected: HashMap<Tag, Money>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Totals {
+    spent: Money,
+    recurring_spent: Money,
+    recurring_due: Money,
+    history: Money,
+}
+
+// a transaction with the same tag and comment in at least two archived months
+#[derive(Debug)]
+struct Recurring {
+    amount: Money,
+    day: u8,
+}
+
+impl Totals {
+    fn new() -> Totals {
+        Totals {
+            spent: Money::from_cents(0),
+            recurring_spent: Money::from_cents(0),
+            recurring_due: Money::from_cents(0),
+            history: Money::from_cents(0),
+        }
+    }
+}
+
+impl Forecast {
+    pub fn new(
+        period: Period,
+        as_of: Date,
+        current: &[Transaction],
+        history: &[(Month, Transactions)],
+    ) -> Forecast {
+        let recurring = find_recurring(history);
+        let mut totals: HashMap<Tag, Totals> = HashMap::new();
+
+        let current: Vec<&Transaction> =
+            current.iter().filter(|t| period.contains(t.date)).collect();
+
+        for t in &current {
+            let is_recurring = recurring.contains_key(&(t.tag.clone(), t.comment.clone()));
+            add_to_tag_and_parents(&mut totals, t.tag(), |totals| {
+                totals.spent += t.amount;
+                if is_recurring {
+                    totals.recurring_spent += t.amount;
+                }
+            });
+        }
+
+        for ((tag, comment), item) in &recurring {
+            let already_spent = current
+                .iter()
+                .any(|t| t.tag == *tag && t.comment == *comment);
+            let due = as_of.with_day(item.day);
+
+            if !already_spent && due >= as_of && period.contains(due) {
+                add_to_tag_and_parents(&mut totals, tag.as_slice(), |totals| {
+                    totals.recurring_due += item.amount;
+                });
+            }
+        }
+
+        let mut history_days: i64 = 0;
+        for (month, transactions) in history {
+            history_days += i64::from(Period::month_of(month.first_day()).days());
+
+            for t in &transactions.transactions {
+                if recurring.contains_key(&(t.tag.clone(), t.comment.clone())) {
+                    continue;
+                }
+
+                add_to_tag_and_parents(&mut totals, t.tag(), |totals| {
+                    totals.history += t.amount;
+                });
+            }
+        }
+
+        let days_total = i64::from(period.days());
+        let days_elapsed = i64::from(period.days_elapsed(as_of));
+        let days_future = days_total - days_elapsed;
+
+        let projected = totals
+            .into_iter()
+            .filter_map(|(tag, totals)| {
+                let non_recurring = totals.spent.checked_sub(totals.recurring_spent)?;
+
+                let future = if history_days > 0 {
+                    // the current pace is weighted by how much of the period
+                    // has elapsed, and the historical pace by how much is left
+                    let from_current = non_recurring.checked_scale(days_future, days_total)?;
+                    let from_history = totals.history.checked_scale(
+                        days_future.checked_mul(days_future)?,
+                        history_days.checked_mul(days_total)?,
+                    )?;
+                    from_current.checked_add(from_history)?
+                } else if days_elapsed > 0 {
+                    non_recurring.checked_scale(days_future, days_elapsed)?
+                } else {
+                    Money::from_cents(0)
+                };
+
+                let projected = totals
+                    .spent
+                    .checked_add(totals.recurring_due)?
+                    .checked_add(future)?;
+
+                Some((tag, projected))
+            })
+            .collect();
+
+        return Forecast { projected };
+    }
+
+    pub fn projected(&self, tag: TagSlice) -> Option<Money> {
+        self.projected.get(&tag.to_tag()).copied()
+    }
+}
+
+fn add_to_tag_and_parents<F>(totals: &mut HashMap<Tag, Totals>, tag: TagSlice, f: F)
+where
+    F: Fn(&mut Totals),
+{
+    f(totals.entry(tag.to_tag()).or_insert_with(Totals::new));
+
+    for p in tag.parents() {
+        f(totals.entry(p.to_tag()).or_insert_with(Totals::new));
+    }
+}
+
+// transactions without a comment cannot be told apart from one-off purchases
+// in the same tag, so they are never considered recurring
+fn find_recurring(history: &[(Month, Transactions)]) -> HashMap<(Tag, String), Recurring> {
+    let mut months_seen: HashMap<(Tag, String), HashSet<Month>> = HashMap::new();
+    let mut latest: HashMap<(Tag, String), Recurring> = HashMap::new();
+
+    for (month, transactions) in history {
+        for t in &transactions.transactions {
+            if t.comment.is_empty() {
+                continue;
+            }
+
+            let key = (t.tag.clone(), t.comment.clone());
+            months_seen.entry(key.clone()).or_default().insert(*month);
+            latest.insert(
+                key,
+                Recurring {
+                    amount: t.amount,
+                    day: t.date.day(),
+                },
+            );
+        }
+    }
+
+    latest.retain(|key, _| months_seen[key].len() >= 2);
+    return latest;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn month(s: &str, transactions: Vec<Transaction>) -> (Month, Transactions) {
+        (Month::from_str(s).unwrap(), Transactions { transactions })
+    }
+
+    fn projected(forecast: &Forecast, tag: &str) -> Option<Money> {
+        forecast.projected(Tag::from_str(tag).unwrap().as_slice())
+    }
+
+    #[test]
+    fn test_current_pace_without_history() {
+        let as_of = Date::from_str("2025-09-10").unwrap();
+        let current = vec![Transaction::sample(
+            "2025-09-05",
+            10_000,
+            "food.groceries",
+            "",
+        )];
+        let forecast = Forecast::new(Period::month_of(as_of), as_of, &current, &[]);
+
+        assert_eq!(
+            projected(&forecast, "food.groceries"),
+            Some(Money::from_cents(30_000))
+        );
+        assert_eq!(
+            projected(&forecast, "food"),
+            Some(Money::from_cents(30_000))
+        );
+    }
+
+    #[test]
+    fn test_recurring_items_still_due() {
+        let as_of = Date::from_str("2025-09-10").unwrap();
+        let history = vec![
+            month(
+                "2025-07",
+                vec![Transaction::sample(
+                    "2025-07-20",
+                    1_500,
+                    "media",
+                    "streaming",
+                )],
+            ),
+            month(
+                "2025-08",
+                vec![Transaction::sample(
+                    "2025-08-20",
+                    1_600,
+                    "media",
+                    "streaming",
+                )],
+            ),
+        ];
+        let forecast = Forecast::new(Period::month_of(as_of), as_of, &[], &history);
+
+        assert_eq!(
+            projected(&forecast, "media"),
+            Some(Money::from_cents(1_600))
+        );
+
+        let current = vec![Transaction::sample(
+            "2025-09-08",
+            1_600,
+            "media",
+            "streaming",
+        )];
+        let forecast = Forecast::new(Period::month_of(as_of), as_of, &current, &history);
+
+        assert_eq!(
+            projected(&forecast, "media"),
+            Some(Money::from_cents(1_600))
+        );
+    }
+
+    #[test]
+    fn test_history_blends_with_current_pace() {
+        let as_of = Date::from_str("2025-09-15").unwrap();
+        let history = vec![month(
+            "2025-08",
+            vec![Transaction::sample("2025-08-03", 31_000, "food", "")],
+        )];
+        let current = vec![Transaction::sample("2025-09-02", 3_000, "food", "")];
+        let forecast = Forecast::new(Period::month_of(as_of), as_of, &current, &history);
+
+        // 15 days left: 3000 * 15 / 30 from the current pace, plus
+        // 31000 * 15 * 15 / (31 * 30) from August's pace
+        assert_eq!(
+            projected(&forecast, "food"),
+            Some(Money::from_cents(3_000 + 1_500 + 7_500))
+        );
+    }
+}
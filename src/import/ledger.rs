@@ -0,0 +1,525 @@
+use crate::accounting;
+use crate::date::Date;
+use crate::money::Money;
+use crate::transaction::{Transaction, TransactionId};
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::str::FromStr;
+
+// the words that follow the date in beancount directives that are not
+// transactions
+const DIRECTIVES: [&str; 11] = [
+    "open",
+    "close",
+    "balance",
+    "pad",
+    "note",
+    "document",
+    "price",
+    "event",
+    "commodity",
+    "custom",
+    "query",
+];
+
+// what a ledger, hledger or beancount journal has to say about budgets: the
+// postings to accounts under the prefix, one transaction each, with the
+// entry's payee or narration as the comment. an id in the entry's metadata,
+// such as budget export writes, is kept when the entry has a single such
+// posting. includes, automated and periodic transactions are not followed
+pub fn parse(contents: &str, prefix: &str) -> Result<Vec<Transaction>, Box<dyn Error>> {
+    let mut parser = Parser::new(prefix);
+    for line in contents.lines() {
+        parser.line(line)?;
+    }
+    parser.finish_entry()?;
+
+    if parser.commodities.len() > 1 {
+        let commodities: Vec<&str> = parser.commodities.iter().map(String::as_str).collect();
+        return Err(format!(
+            "the expenses are in {}, but a budget only has one currency",
+            commodities.join(" and ")
+        )
+        .into());
+    }
+
+    return Ok(parser.transactions);
+}
+
+struct Entry {
+    line: usize,
+    date: Date,
+    description: String,
+    id: Option<TransactionId>,
+    postings: Vec<Posting>,
+}
+
+struct Posting {
+    account: String,
+    // the amount and its commodity, unless it is left for the entry to balance
+    amount: Option<(Money, String)>,
+}
+
+struct Parser<'a> {
+    prefix: &'a str,
+    amount: Regex,
+    metadata: Regex,
+    line: usize,
+    entry: Option<Entry>,
+    in_comment_block: bool,
+    transactions: Vec<Transaction>,
+    commodities: BTreeSet<String>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(prefix: &'a str) -> Parser<'a> {
+        Parser {
+            prefix,
+            // a sign, a commodity and a sign again may come before the number,
+            // as in -$45.12 and $-45.12, or the commodity may come after it
+            amount: Regex::new(
+                r"^(-)?\s*([^\d\s.,+-]*)\s*([+-])?\s*(\d[\d,]*(?:\.\d*)?|\.\d+)\s*(\S*)$",
+            )
+            .unwrap(),
+            metadata: Regex::new(r"^([a-z][A-Za-z0-9_-]*):(?:\s+(.*))?$").unwrap(),
+            line: 0,
+            entry: None,
+            in_comment_block: false,
+            transactions: Vec::new(),
+            commodities: BTreeSet::new(),
+        }
+    }
+
+    fn line(&mut self, line: &str) -> Result<(), Box<dyn Error>> {
+        self.line += 1;
+        let number = self.line;
+        let at = |e: Box<dyn Error>| format!("line {}: {}", number, e);
+
+        if self.in_comment_block {
+            self.in_comment_block = line.trim_end() != "end comment";
+            return Ok(());
+        }
+
+        if line.starts_with([' ', '\t']) {
+            return Ok(self.indented(line.trim()).map_err(at)?);
+        }
+
+        self.finish_entry()?;
+
+        if line.trim_end() == "comment" {
+            self.in_comment_block = true;
+            return Ok(());
+        }
+
+        if line.starts_with(|c: char| c.is_ascii_digit()) {
+            self.entry = self.header(line).map_err(at)?;
+        }
+
+        Ok(())
+    }
+
+    // returns None for beancount directives, whose indented lines are then
+    // skipped along with them
+    fn header(&self, line: &str) -> Result<Option<Entry>, Box<dyn Error>> {
+        let (date, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+        // the date after = is hledger's secondary date
+        let date = date
+            .split('=')
+            .next()
+            .unwrap_or(date)
+            .replace(['/', '.'], "-");
+        let date = Date::from_str(&date).map_err(|_| format!("{} is not a date", date))?;
+
+        let mut rest = rest.trim();
+        let keyword = rest.split_whitespace().next().unwrap_or("");
+        if DIRECTIVES.contains(&keyword) {
+            return Ok(None);
+        }
+
+        for flag in ["txn ", "* ", "! "] {
+            rest = rest.strip_prefix(flag).unwrap_or(rest).trim_start();
+        }
+        if rest == "*" || rest == "!" || rest == "txn" {
+            rest = "";
+        }
+        if rest.starts_with('(') {
+            rest = rest
+                .split_once(')')
+                .map_or("", |(_, after)| after.trim_start());
+        }
+
+        let description = if rest.starts_with('"') {
+            beancount_description(rest)
+        } else {
+            strip_comment(rest).to_string()
+        };
+
+        return Ok(Some(Entry {
+            line: self.line,
+            date,
+            description,
+            id: None,
+            postings: Vec::new(),
+        }));
+    }
+
+    fn indented(&mut self, line: &str) -> Result<(), Box<dyn Error>> {
+        let Some(entry) = &mut self.entry else {
+            return Ok(());
+        };
+
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        // ledger and hledger keep metadata in comments, beancount keeps it
+        // in lines of its own
+        if let Some(comment) = line.strip_prefix(';').or_else(|| line.strip_prefix('#')) {
+            // budget export writes a comment that cannot be a payee as a note
+            match comment.split_once(':') {
+                Some((key, value)) if key.trim() == "id" => {
+                    entry.id = TransactionId::from_str(value.trim()).ok();
+                }
+                Some((key, value)) if key.trim() == "note" => {
+                    entry.description = value.trim().to_string();
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        if let Some(captures) = self.metadata.captures(line) {
+            if &captures[1] == "id" {
+                let value = captures.get(2).map_or("", |v| v.as_str());
+                entry.id = TransactionId::from_str(value.trim().trim_matches('"')).ok();
+            }
+            return Ok(());
+        }
+
+        let mut posting = strip_comment(line);
+        for flag in ["* ", "! "] {
+            posting = posting.strip_prefix(flag).unwrap_or(posting).trim_start();
+        }
+
+        // ledger separates the account from the amount with two spaces or a
+        // tab, since its accounts may contain single spaces; beancount's may
+        // not, and a single space is enough there
+        let (account, amount) = match posting.find("  ").or_else(|| posting.find('\t')) {
+            Some(end) => (&posting[..end], posting[end..].trim()),
+            None => match posting.split_once(' ') {
+                Some((account, amount)) if self.amount.is_match(amount_part(amount)) => {
+                    (account, amount.trim())
+                }
+                _ => (posting, ""),
+            },
+        };
+
+        let account = account
+            .trim_matches(|c| c == '(' || c == ')' || c == '[' || c == ']')
+            .to_string();
+        let amount = match amount {
+            "" => None,
+            amount => Some(parse_amount(&self.amount, amount)?),
+        };
+        entry.postings.push(Posting { account, amount });
+
+        Ok(())
+    }
+
+    fn finish_entry(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(mut entry) = self.entry.take() else {
+            return Ok(());
+        };
+        let line = entry.line;
+        let at = |e: String| format!("the entry on line {}: {}", line, e);
+
+        balance(&mut entry.postings).map_err(at)?;
+
+        let mut selected = Vec::new();
+        for posting in entry.postings {
+            // every posting has an amount once the entry is balanced
+            let (Some(tag), Some(amount)) = (
+                accounting::tag_of(&posting.account, self.prefix).map_err(at)?,
+                posting.amount,
+            ) else {
+                continue;
+            };
+            selected.push((tag, amount));
+        }
+
+        let id = match selected.len() {
+            1 => entry.id.unwrap_or_default(),
+            _ => TransactionId::default(),
+        };
+        for (tag, (amount, commodity)) in selected {
+            self.commodities.insert(commodity);
+            self.transactions.push(Transaction {
+                date: entry.date,
+                amount,
+                tag,
+                comment: entry.description.clone(),
+                id: id.clone(),
+                bank_id: None,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+// costs, prices and balance assertions that follow the amount are
+// ignored
+// the amount itself, without a price or balance assertion after it
+fn amount_part(text: &str) -> &str {
+    text.split(['@', '{', '=']).next().unwrap_or(text).trim()
+}
+
+fn parse_amount(pattern: &Regex, text: &str) -> Result<(Money, String), Box<dyn Error>> {
+    let text = amount_part(text);
+    let captures = pattern
+        .captures(text)
+        .ok_or_else(|| format!("{} is not an amount", text))?;
+
+    let (before, after) = (&captures[2], &captures[5]);
+    if !before.is_empty() && !after.is_empty() {
+        return Err(format!("{} has two commodities", text).into());
+    }
+
+    let negative = captures.get(1).is_some() ^ captures.get(3).is_some_and(|s| s.as_str() == "-");
+    // zeros past the cents are common, as in 10.000 EUR, and lose nothing
+    let mut number = captures[4].replace(',', "");
+    if let Some((whole, decimals)) = number.split_once('.') {
+        let decimals = decimals.trim_end_matches('0');
+        if decimals.len() > 2 {
+            return Err(format!(
+                "{} has fractions of a cent, which a budget cannot keep",
+                text
+            )
+            .into());
+        }
+        number = format!("{}.{}", whole, decimals);
+    }
+    let amount = Money::from_decimal(number.trim_end_matches('.'))
+        .map_err(|e| format!("{}: {}", text, e))?;
+    let amount = if negative {
+        Money::from_cents(0)
+            .checked_sub(amount)
+            .ok_or("the amount overflowed")?
+    } else {
+        amount
+    };
+
+    return Ok((amount, format!("{}{}", before, after)));
+}
+
+// a posting without an amount takes whatever balances the entry
+fn balance(postings: &mut [Posting]) -> Result<(), String> {
+    let missing: Vec<usize> = (0..postings.len())
+        .filter(|i| postings[*i].amount.is_none())
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    if missing.len() > 1 {
+        return Err("more than one posting is left without an amount".to_string());
+    }
+
+    let mut total = Money::from_cents(0);
+    let mut commodities = BTreeSet::new();
+    for (amount, commodity) in postings.iter().filter_map(|p| p.amount.as_ref()) {
+        total = total.checked_add(*amount).ok_or("the total overflowed")?;
+        commodities.insert(commodity.clone());
+    }
+    if commodities.len() != 1 {
+        return Err("the posting without an amount has no single commodity to balance".to_string());
+    }
+
+    let balancing = Money::from_cents(0)
+        .checked_sub(total)
+        .ok_or("the total overflowed")?;
+    postings[missing[0]].amount = Some((balancing, commodities.pop_first().unwrap()));
+
+    Ok(())
+}
+
+// everything from the first semicolon on is a comment
+fn strip_comment(text: &str) -> &str {
+    let end = text.find(';').unwrap_or(text.len());
+
+    return text[..end].trim();
+}
+
+// the payee and narration, joined, without any tags or links after them
+fn beancount_description(text: &str) -> String {
+    let mut strings = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+
+        let mut string = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => string.extend(chars.next()),
+                c => string.push(c),
+            }
+        }
+        strings.push(string);
+    }
+    strings.retain(|s| !s.is_empty());
+
+    return strings.join(": ");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tag::Tag;
+
+    fn summary(transactions: &[Transaction]) -> Vec<(String, String, Money, String)> {
+        transactions
+            .iter()
+            .map(|t| {
+                (
+                    t.date.to_string(),
+                    t.tag.to_dotted_string(),
+                    t.amount,
+                    t.comment.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ledger() {
+        let journal = "\
+; a comment
+2025/09/12 * (101) Grocer  ; paid by card
+    Expenses:Food:Groceries      $45.12
+    Expenses:Household          $ 10
+    Assets:Checking
+
+2025-09-13=2025-09-14 Refund
+    Expenses:Food:Fast Food   -$3.50
+    ; id:0000000a
+    Assets:Checking    $3.50 = $100.00
+
+comment
+2025-09-15 Hidden
+    Expenses:Food  $1
+end comment
+";
+        let transactions = parse(journal, "Expenses:").unwrap();
+        assert_eq!(
+            summary(&transactions),
+            vec![
+                (
+                    "2025-09-12".into(),
+                    "food.groceries".into(),
+                    Money::from_cents(4_512),
+                    "Grocer".into()
+                ),
+                (
+                    "2025-09-12".into(),
+                    "household".into(),
+                    Money::from_cents(1_000),
+                    "Grocer".into()
+                ),
+                (
+                    "2025-09-13".into(),
+                    "food.fast-food".into(),
+                    Money::from_cents(-350),
+                    "Refund".into()
+                ),
+            ]
+        );
+        assert_eq!(
+            transactions[2].id,
+            TransactionId::from_str("0000000a").unwrap()
+        );
+        assert!(!transactions[0].id.is_assigned());
+    }
+
+    #[test]
+    fn test_exported_notes_are_comments() {
+        let journal = "\
+2025-09-12 food.fast-food
+    ; note: (refund); or so
+    ; id: 0000000a
+    Expenses:Food:Fast-Food  -3.50 USD
+    Assets:Checking  3.50 USD
+";
+        let transactions = parse(journal, "Expenses:").unwrap();
+        assert_eq!(transactions[0].comment, "(refund); or so");
+        assert_eq!(
+            transactions[0].id,
+            TransactionId::from_str("0000000a").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_beancount() {
+        let journal = "\
+option \"operating_currency\" \"USD\"
+2025-01-01 open Expenses:Food:Coffee
+  note: \"ignored\"
+2025-09-12 * \"Cafe\" \"the \\\"usual\\\"\" #work
+  id: \"0000000b\"
+  Expenses:Food:Coffee 4.50 USD
+  Assets:Checking
+2025-09-13 balance Assets:Checking 100.00 USD
+";
+        let transactions = parse(journal, "Expenses").unwrap();
+        assert_eq!(
+            summary(&transactions),
+            vec![(
+                "2025-09-12".into(),
+                "food.coffee".into(),
+                Money::from_cents(450),
+                "Cafe: the \"usual\"".into()
+            )]
+        );
+        assert_eq!(transactions[0].tag, Tag::from_str("food.coffee").unwrap());
+        assert_eq!(
+            transactions[0].id,
+            TransactionId::from_str("0000000b").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let two_elided = "2025-09-12 x\n    Expenses:Food\n    Assets:Checking\n";
+        assert!(parse(two_elided, "Expenses:").is_err());
+
+        let two_currencies = "2025-09-12 x\n    Expenses:Food  4.50 USD\n    Expenses:Fun  3 EUR\n    Assets:Cash  -4.50 USD\n    Assets:Cash  -3 EUR\n";
+        assert!(parse(two_currencies, "Expenses:").is_err());
+
+        let bad_date = "2025-13-01 x\n    Expenses:Food  $1\n    Assets:Cash\n";
+        let error = parse(bad_date, "Expenses:").unwrap_err().to_string();
+        assert!(error.starts_with("line 1:"), "{}", error);
+
+        let fraction = "2025-09-12 x\n    Expenses:Food  10.005 EUR\n    Assets:Cash\n";
+        let error = parse(fraction, "Expenses:").unwrap_err().to_string();
+        assert!(error.starts_with("line 2:"), "{}", error);
+        let beancount = "2025-09-12 * \"x\"\n  Expenses:Food 10.005 EUR\n  Assets:Cash\n";
+        let error = parse(beancount, "Expenses:").unwrap_err().to_string();
+        assert!(error.starts_with("line 2:"), "{}", error);
+    }
+
+    #[test]
+    fn test_trailing_zeros() {
+        let journal = "2025-09-12 x\n    Expenses:Food  10.000 EUR\n    Expenses:Fun 2.50 EUR\n    Assets:Cash  -12.500 EUR\n";
+        let amounts: Vec<Money> = parse(journal, "Expenses:")
+            .unwrap()
+            .iter()
+            .map(|t| t.amount)
+            .collect();
+        assert_eq!(
+            amounts,
+            vec![Money::from_cents(1_000), Money::from_cents(250)]
+        );
+    }
+}
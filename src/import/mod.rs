@@ -0,0 +1,159 @@
+use crate::limits::Limits;
+use crate::tag::Tag;
+use crate::transaction::Transaction;
+use std::collections::BTreeSet;
+use std::error::Error;
+
+pub mod ledger;
+pub mod ofx;
+
+// which of the imported transactions the budget already has: those with the
+// bank id of one of its transactions, or its id and the same date, tag and
+// amount, or else the same date, tag, amount and comment as one that did not
+// come from a bank. ids are only unique within a file, so an id on its own
+// could be another budget's transaction. each existing transaction accounts
+// for at most one imported one, so a file with three identical coffees adds
+// one to a budget that has two
+pub fn duplicates(existing: &[Transaction], imported: &[Transaction]) -> Vec<bool> {
+    let mut unclaimed: Vec<&Transaction> = existing.iter().collect();
+    let mut found = vec![false; imported.len()];
+
+    for (i, t) in imported.iter().enumerate() {
+        let same = unclaimed.iter().position(|e| {
+            let same_id = t.id.is_assigned()
+                && e.id == t.id
+                && e.date == t.date
+                && e.tag == t.tag
+                && e.amount == t.amount;
+            same_id || (t.bank_id.is_some() && e.bank_id == t.bank_id)
+        });
+        if let Some(index) = same {
+            unclaimed.swap_remove(index);
+            found[i] = true;
+        }
+    }
+
+    for (i, t) in imported.iter().enumerate() {
+        if found[i] {
+            continue;
+        }
+        let same = unclaimed.iter().position(|e| {
+            e.bank_id.is_none()
+                && e.date == t.date
+                && e.tag == t.tag
+                && e.amount == t.amount
+                && e.comment == t.comment
+        });
+        if let Some(index) = same {
+            unclaimed.swap_remove(index);
+            found[i] = true;
+        }
+    }
+
+    return found;
+}
+
+// only leaves may have transactions, so the new ones may neither go on a tag
+// that is the parent of another, nor make a parent of a tag that has some
+pub fn check_tags(
+    existing: &[Transaction],
+    limits: &Limits,
+    new: &[Transaction],
+) -> Result<(), Box<dyn Error>> {
+    let new_tags: BTreeSet<&Tag> = new.iter().map(|t| &t.tag).collect();
+    let with_transactions: BTreeSet<&Tag> = existing
+        .iter()
+        .map(|t| &t.tag)
+        .chain(new_tags.iter().copied())
+        .collect();
+    let all: BTreeSet<&Tag> = with_transactions
+        .iter()
+        .copied()
+        .chain(limits.limits.keys())
+        .collect();
+
+    for tag in &with_transactions {
+        let child = all
+            .iter()
+            .find(|other| tag.as_slice().is_ancestor_of(other.as_slice()));
+        let Some(child) = child else {
+            continue;
+        };
+
+        if new_tags.contains(tag) || new_tags.contains(child) {
+            return Err(format!(
+                "{} would have transactions but also be the parent of {}",
+                tag.to_dotted_string(),
+                child.to_dotted_string()
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Money;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    fn coffee(tag: &str, id: &str) -> Transaction {
+        Transaction::sample("2025-09-12", 450, tag, "").with_id(id)
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let existing = vec![
+            coffee("food.coffee", "0000000a"),
+            coffee("food.coffee", "0000000b"),
+        ];
+        let imported = vec![
+            coffee("food.coffee", ""),
+            coffee("food.coffee", ""),
+            coffee("food.coffee", ""),
+            coffee("food.tea", "0000000a"),
+            Transaction {
+                comment: "edited since".to_string(),
+                ..coffee("food.coffee", "0000000b")
+            },
+        ];
+
+        // the tea only shares an id with the first coffee, so it is new. the
+        // edited coffee claims the second one by its id, which leaves one
+        // coffee for the three identical ones
+        assert_eq!(
+            duplicates(&existing, &imported),
+            vec![true, false, false, false, true]
+        );
+    }
+
+    #[test]
+    fn test_bank_duplicates() {
+        let from_bank = |bank_id: &str| Transaction {
+            bank_id: Some(bank_id.to_string()),
+            ..coffee("food.coffee", "")
+        };
+        let existing = vec![from_bank("1"), coffee("food.coffee", "0000000a")];
+        let imported = vec![from_bank("1"), from_bank("2"), from_bank("3")];
+
+        // the coffee from the bank can only stand in for the same bank
+        // transaction, while the one added by hand stands in for any
+        assert_eq!(duplicates(&existing, &imported), vec![true, true, false]);
+    }
+
+    #[test]
+    fn test_check_tags() {
+        let existing = vec![coffee("food.coffee", "0000000a")];
+        let limits = Limits {
+            limits: BTreeMap::from([(Tag::from_str("fun.games").unwrap(), Money::from_cents(100))]),
+        };
+
+        assert!(check_tags(&existing, &limits, &[coffee("food.tea", "")]).is_ok());
+        assert!(check_tags(&existing, &limits, &[coffee("food", "")]).is_err());
+        assert!(check_tags(&existing, &limits, &[coffee("food.coffee.decaf", "")]).is_err());
+        assert!(check_tags(&existing, &limits, &[coffee("fun", "")]).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+use crate::date::Date;
+use crate::money::Money;
+use std::collections::HashMap;
+use std::error::Error;
+use std::str::FromStr;
+
+// one STMTTRN record of a bank statement, as the bank sees it: money that
+// left the account is negative
+#[derive(Debug, PartialEq)]
+pub struct BankTransaction {
+    pub date: Date,
+    pub amount: Money,
+    pub name: String,
+    pub memo: String,
+    // the FITID, which is only unique within an account, so the account's
+    // ACCTID comes first when the statement has one
+    pub bank_id: String,
+}
+
+impl BankTransaction {
+    // what rules are matched against, and the transaction's comment
+    pub fn description(&self) -> String {
+        match (self.name.as_str(), self.memo.as_str()) {
+            (name, "") => name.to_string(),
+            ("", memo) => memo.to_string(),
+            (name, memo) if name == memo => name.to_string(),
+            (name, memo) => format!("{}: {}", name, memo),
+        }
+    }
+}
+
+// reads both OFX 1, which is SGML and leaves elements unclosed, and OFX 2,
+// which is XML; QFX is OFX with a few elements of Quicken's own. only the
+// elements are looked at, so the headers of either kind are skipped
+pub fn parse(contents: &str) -> Result<Vec<BankTransaction>, Box<dyn Error>> {
+    let start = contents
+        .find("<OFX>")
+        .ok_or("there is no <OFX> element; is it an OFX or QFX file?")?;
+
+    let mut transactions = Vec::new();
+    let mut account = None;
+    // only the statement's own account counts; a transfer names the account
+    // on the other side in BANKACCTTO or CCACCTTO
+    let mut in_account = false;
+    let mut record: Option<HashMap<String, String>> = None;
+
+    for element in contents[start..].split('<').skip(1) {
+        let (name, text) = element
+            .split_once('>')
+            .ok_or_else(|| format!("<{} is not closed", element.trim()))?;
+        let name = name.trim().to_uppercase();
+        let text = decode(text.trim());
+
+        match name.as_str() {
+            "STMTTRN" => record = Some(HashMap::new()),
+            "/STMTTRN" => {
+                let fields = record.take().ok_or("</STMTTRN> has no <STMTTRN>")?;
+                let number = transactions.len() + 1;
+                let transaction = transaction(fields, account.as_deref())
+                    .map_err(|e| format!("transaction {} of the statement: {}", number, e))?;
+                transactions.push(transaction);
+            }
+            "BANKACCTFROM" | "CCACCTFROM" => in_account = record.is_none(),
+            "/BANKACCTFROM" | "/CCACCTFROM" => in_account = false,
+            "ACCTID" if in_account => account = Some(text),
+            _ => {
+                if let Some(fields) = &mut record
+                    && !name.starts_with('/')
+                    && !text.is_empty()
+                {
+                    fields.insert(name, text);
+                }
+            }
+        }
+    }
+
+    return Ok(transactions);
+}
+
+fn transaction(
+    mut fields: HashMap<String, String>,
+    account: Option<&str>,
+) -> Result<BankTransaction, Box<dyn Error>> {
+    let mut field = |name: &str| {
+        fields
+            .remove(name)
+            .ok_or_else(|| format!("it has no {}", name))
+    };
+
+    // e.g. 20250912, 20250912120000 or 20250912120000.000[-5:EST]
+    let posted = field("DTPOSTED")?;
+    let date = posted
+        .get(..8)
+        .filter(|d| d.bytes().all(|b| b.is_ascii_digit()))
+        .map(|d| format!("{}-{}-{}", &d[..4], &d[4..6], &d[6..]))
+        .and_then(|d| Date::from_str(&d).ok())
+        .ok_or_else(|| format!("{} is not a date", posted))?;
+
+    let amount = field("TRNAMT")?;
+    let amount = parse_amount(&amount).ok_or_else(|| format!("{} is not an amount", amount))?;
+
+    let fitid = field("FITID")?;
+    let bank_id = match account {
+        Some(account) => format!("{}:{}", account, fitid),
+        None => fitid,
+    };
+
+    return Ok(BankTransaction {
+        date,
+        amount,
+        name: field("NAME").unwrap_or_default(),
+        memo: field("MEMO").unwrap_or_default(),
+        bank_id,
+    });
+}
+
+// some banks write a decimal comma, and some more decimals than there are
+// cents, all of them zeros
+fn parse_amount(text: &str) -> Option<Money> {
+    let mut text = match text.contains('.') {
+        true => text.replace(',', ""),
+        false => text.replace(',', "."),
+    };
+
+    if let Some((whole, decimals)) = text.split_once('.') {
+        let decimals = decimals.trim_end_matches('0');
+        text = format!("{}.{}", whole, decimals);
+    }
+
+    return Money::from_decimal(text.trim_end_matches('.')).ok();
+}
+
+fn decode(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sgml() {
+        let statement = "\
+OFXHEADER:100
+DATA:OFXSGML
+
+<OFX>
+<BANKMSGSRSV1><STMTTRNRS><STMTRS>
+<BANKACCTFROM><BANKID>123<ACCTID>9876<ACCTTYPE>CHECKING</BANKACCTFROM>
+<BANKTRANLIST>
+<STMTTRN>
+<TRNTYPE>DEBIT
+<DTPOSTED>20250912120000.000[-5:EST]
+<TRNAMT>-4.5
+<FITID>20250912001
+<NAME>STARBUCKS #123
+<MEMO>STARBUCKS #123
+</STMTTRN>
+<STMTTRN>
+<TRNTYPE>CREDIT
+<DTPOSTED>20250913
+<TRNAMT>12,00
+<FITID>20250913001
+<NAME>Refund
+<MEMO>Tom &amp; Jerry&apos;s
+</STMTTRN>
+</BANKTRANLIST>
+</STMTRS></STMTTRNRS></BANKMSGSRSV1>
+</OFX>
+";
+        let transactions = parse(statement).unwrap();
+        assert_eq!(transactions.len(), 2);
+
+        assert_eq!(transactions[0].date, Date::from_str("2025-09-12").unwrap());
+        assert_eq!(transactions[0].amount, Money::from_cents(-450));
+        assert_eq!(transactions[0].bank_id, "9876:20250912001");
+        assert_eq!(transactions[0].description(), "STARBUCKS #123");
+
+        assert_eq!(transactions[1].amount, Money::from_cents(1_200));
+        assert_eq!(transactions[1].description(), "Refund: Tom & Jerry's");
+    }
+
+    #[test]
+    fn test_transfers_keep_the_statement_account() {
+        let statement = "<OFX><STMTRS>\
+            <BANKACCTFROM><BANKID>123<ACCTID>9876<ACCTTYPE>CHECKING</BANKACCTFROM>\
+            <BANKTRANLIST>\
+            <STMTTRN><TRNTYPE>XFER<DTPOSTED>20250912<TRNAMT>-50.00<FITID>1\
+            <BANKACCTTO><BANKID>123<ACCTID>5555<ACCTTYPE>SAVINGS</BANKACCTTO></STMTTRN>\
+            <STMTTRN><DTPOSTED>20250913<TRNAMT>-4.50<FITID>2<NAME>Cafe</STMTTRN>\
+            </BANKTRANLIST></STMTRS></OFX>";
+        let transactions = parse(statement).unwrap();
+
+        assert_eq!(transactions[0].bank_id, "9876:1");
+        assert_eq!(transactions[1].bank_id, "9876:2");
+    }
+
+    #[test]
+    fn test_xml() {
+        let statement = "<?xml version=\"1.0\"?><?OFX OFXHEADER=\"200\"?>\
+            <OFX><CREDITCARDMSGSRSV1><CCSTMTTRNRS><CCSTMTRS><BANKTRANLIST>\
+            <STMTTRN><DTPOSTED>20250914</DTPOSTED><TRNAMT>-100.2500</TRNAMT>\
+            <FITID>a1</FITID><NAME>Grocer</NAME></STMTTRN>\
+            </BANKTRANLIST></CCSTMTRS></CCSTMTTRNRS></CREDITCARDMSGSRSV1></OFX>";
+        let transactions = parse(statement).unwrap();
+
+        assert_eq!(
+            transactions,
+            vec![BankTransaction {
+                date: Date::from_str("2025-09-14").unwrap(),
+                amount: Money::from_cents(-10_025),
+                name: "Grocer".to_string(),
+                memo: String::new(),
+                bank_id: "a1".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(parse("OFXHEADER:100").is_err());
+        assert!(parse("<OFX><STMTTRN><DTPOSTED>20250914<TRNAMT>1.00</STMTTRN></OFX>").is_err());
+        assert!(parse("<OFX><STMTTRN><DTPOSTED>2025<TRNAMT>1<FITID>1</STMTTRN></OFX>").is_err());
+        assert!(
+            parse("<OFX><STMTTRN><DTPOSTED>20250914<TRNAMT>1.005<FITID>1</STMTTRN></OFX>").is_err()
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use crate::atomic_file;
+use crate::date::Date;
+use crate::limits::Limits;
+use crate::transaction::{Transaction, Transactions};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+
+// only the most recent entries are kept
+const JOURNAL_LENGTH: usize = 100;
+
+// every change to the transactions and limits, oldest first. the entries
+// before `position` are applied; the ones after it have been undone and can
+// be redone until something new is recorded
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+pub struct Journal {
+    entries: Vec<Entry>,
+    position: usize,
+}
+
+// a change is stored as the transactions it took out and put in, which is
+// enough to apply it in either direction
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub date: Date,
+    pub description: String,
+    #[serde(default)]
+    pub removed: Vec<Transaction>,
+    #[serde(default)]
+    pub added: Vec<Transaction>,
+    #[serde(default)]
+    pub limits: Option<LimitsChange>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct LimitsChange {
+    pub before: Limits,
+    pub after: Limits,
+}
+
+impl Entry {
+    pub fn transactions(
+        description: String,
+        removed: Vec<Transaction>,
+        added: Vec<Transaction>,
+    ) -> Entry {
+        Entry {
+            date: Date::today(),
+            description,
+            removed,
+            added,
+            limits: None,
+        }
+    }
+
+    pub fn limits(description: String, before: Limits, after: Limits) -> Entry {
+        Entry {
+            date: Date::today(),
+            description,
+            removed: Vec::new(),
+            added: Vec::new(),
+            limits: Some(LimitsChange { before, after }),
+        }
+    }
+
+    // the transactions are only swapped when the ones to take out are still
+    // exactly as recorded, and the limits only when they are still as they
+    // were left, so a later change is never silently overwritten
+    pub fn apply(
+        &self,
+        transactions: &mut Transactions,
+        limits: &mut Limits,
+    ) -> Result<(), Box<dyn Error>> {
+        replace(transactions, &self.removed, &self.added)?;
+        if let Some(change) = &self.limits {
+            replace_limits(limits, &change.before, &change.after)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn revert(
+        &self,
+        transactions: &mut Transactions,
+        limits: &mut Limits,
+    ) -> Result<(), Box<dyn Error>> {
+        replace(transactions, &self.added, &self.removed)?;
+        if let Some(change) = &self.limits {
+            replace_limits(limits, &change.after, &change.before)?;
+        }
+
+        Ok(())
+    }
+}
+
+// a plain description of a transaction, since descriptions are stored
+pub fn describe(t: &Transaction) -> String {
+    let mut description = format!(
+        "{} {} {} on {}",
+        t.id,
+        t.tag.to_dotted_string(),
+        t.amount.to_plain_string(),
+        t.date
+    );
+    if !t.comment.is_empty() {
+        description.push_str(&format!(" ({})", t.comment));
+    }
+
+    return description;
+}
+
+fn replace(
+    transactions: &mut Transactions,
+    take_out: &[Transaction],
+    put_in: &[Transaction],
+) -> Result<(), Box<dyn Error>> {
+    for t in take_out {
+        let index = transactions
+            .transactions
+            .iter()
+            .position(|other| other == t)
+            .ok_or_else(|| format!("transaction {} has changed since", t.id))?;
+        transactions.transactions.remove(index);
+    }
+
+    for t in put_in {
+        if transactions
+            .transactions
+            .iter()
+            .any(|other| other.id == t.id)
+        {
+            return Err(format!("transaction {} already exists", t.id).into());
+        }
+        transactions.add(t.clone());
+    }
+
+    Ok(())
+}
+
+fn replace_limits(limits: &mut Limits, from: &Limits, to: &Limits) -> Result<(), Box<dyn Error>> {
+    if limits != from {
+        return Err("the limits have changed since".into());
+    }
+    *limits = to.clone();
+
+    Ok(())
+}
+
+impl Journal {
+    pub fn empty() -> Journal {
+        Journal {
+            entries: Vec::new(),
+            position: 0,
+        }
+    }
+
+    // a budget that has never been changed has no journal yet
+    pub fn from_json_file_or_empty<P>(path: P) -> Result<Self, Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+    {
+        if !path.as_ref().exists() {
+            return Ok(Journal::empty());
+        }
+
+        let file = File::open_buffered(path)?;
+        return Ok(serde_json::from_reader(file)?);
+    }
+
+    pub fn to_json_file<P>(&self, path: P) -> Result<(), Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+    {
+        atomic_file::write(path, &serde_json::to_vec_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    // recording something new drops whatever had been undone
+    pub fn record(&mut self, entry: Entry) {
+        self.entries.truncate(self.position);
+        self.entries.push(entry);
+
+        let excess = self.entries.len().saturating_sub(JOURNAL_LENGTH);
+        self.entries.drain(..excess);
+        self.position = self.entries.len();
+    }
+
+    // the entry to revert next, if anything is left to undo
+    pub fn undo(&mut self) -> Option<&Entry> {
+        if self.position == 0 {
+            return None;
+        }
+
+        self.position -= 1;
+        return Some(&self.entries[self.position]);
+    }
+
+    // the entry to apply again, if anything has been undone
+    pub fn redo(&mut self) -> Option<&Entry> {
+        if self.position == self.entries.len() {
+            return None;
+        }
+
+        self.position += 1;
+        return Some(&self.entries[self.position - 1]);
+    }
+
+    // the most recent entries, newest first, with whether each is applied
+    pub fn recent(&self, count: usize) -> impl Iterator<Item = (usize, &Entry, bool)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .rev()
+            .take(count)
+            .map(|(i, entry)| (i + 1, entry, i < self.position))
+    }
+}
+
+// loads the journal next to the data, records the entry and writes it back
+pub fn record<P>(path: P, entry: Entry) -> Result<(), Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let mut journal = Journal::from_json_file_or_empty(&path)?;
+    journal.record(entry);
+    journal.to_json_file(&path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Money;
+    use crate::tag::Tag;
+    use std::str::FromStr;
+
+    fn coffee(cents: i64) -> Transaction {
+        Transaction::sample("2025-09-12", cents, "food.coffee", "Blue Bottle")
+    }
+
+    #[test]
+    fn test_apply_and_revert() {
+        let before = coffee(450);
+        let mut after = before.clone();
+        after.amount = Money::from_cents(500);
+
+        let mut transactions = Transactions::empty();
+        transactions.add(before.clone());
+
+        let entry = Entry::transactions(
+            "edit".to_string(),
+            vec![before.clone()],
+            vec![after.clone()],
+        );
+        let mut limits = Limits::empty();
+        entry.apply(&mut transactions, &mut limits).unwrap();
+        assert_eq!(transactions.transactions, vec![after.clone()]);
+
+        // applying twice finds the original gone
+        assert!(entry.apply(&mut transactions, &mut limits).is_err());
+
+        entry.revert(&mut transactions, &mut limits).unwrap();
+        assert_eq!(transactions.transactions, vec![before]);
+    }
+
+    #[test]
+    fn test_limits_changed_since() {
+        let limited = |cents| Limits {
+            limits: [(Tag::from_str("food").unwrap(), Money::from_cents(cents))].into(),
+        };
+        let entry = Entry::limits("limit food".to_string(), limited(100), limited(200));
+        let mut transactions = Transactions::empty();
+
+        let mut limits = limited(300);
+        assert!(entry.revert(&mut transactions, &mut limits).is_err());
+        assert!(entry.apply(&mut transactions, &mut limits).is_err());
+        assert_eq!(limits, limited(300));
+
+        let mut limits = limited(200);
+        entry.revert(&mut transactions, &mut limits).unwrap();
+        assert_eq!(limits, limited(100));
+        entry.apply(&mut transactions, &mut limits).unwrap();
+        assert_eq!(limits, limited(200));
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let entry = |description: &str| {
+            Entry::transactions(description.to_string(), Vec::new(), Vec::new())
+        };
+
+        let mut journal = Journal::empty();
+        assert!(journal.undo().is_none());
+
+        journal.record(entry("first"));
+        journal.record(entry("second"));
+        assert_eq!(journal.undo().unwrap().description, "second");
+        assert!(journal.redo().is_some());
+        assert!(journal.redo().is_none());
+
+        journal.undo();
+        journal.record(entry("third"));
+        assert!(journal.redo().is_none());
+
+        let descriptions: Vec<_> = journal
+            .recent(10)
+            .map(|(_, e, _)| e.description.as_str())
+            .collect();
+        assert_eq!(descriptions, vec!["third", "first"]);
+    }
+}
@@ -1,11 +1,47 @@
+use crate::atomic_file;
+use crate::schema::{self, Versioned};
+use crate::tag::TagSlice;
 use crate::{Money, Tag};
-use serde::Deserialize;
-use std::collections::HashMap;
-use std::io::Read;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io::{Read, Write};
+use std::iter;
+use std::path::Path;
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
 pub struct Limits {
-    pub limits: HashMap<Tag, Money>,
+    pub limits: BTreeMap<Tag, Money>,
+}
+
+// problems with how limits divide up their parents' limits
+#[derive(Debug, PartialEq)]
+pub enum LimitIssue {
+    OverAllocated {
+        tag: Tag,
+        limit: Money,
+        children: Money,
+    },
+    // the root is exempt, since it is the parent of every tag
+    MissingLimit {
+        tag: Tag,
+        children: Money,
+    },
+    Unallocated {
+        tag: Tag,
+        remainder: Money,
+    },
+}
+
+impl LimitIssue {
+    // unallocated money is allowed, it is only reported
+    pub fn is_problem(&self) -> bool {
+        match self {
+            LimitIssue::OverAllocated { .. } | LimitIssue::MissingLimit { .. } => true,
+            LimitIssue::Unallocated { .. } => false,
+        }
+    }
 }
 
 impl Limits {
@@ -16,4 +52,351 @@ impl Limits {
         let limits: Self = serde_json::from_reader(r)?;
         return Ok(limits);
     }
+
+    pub fn to_json_writer<W>(&self, w: W) -> Result<(), serde_json::Error>
+    where
+        W: Write,
+    {
+        let versioned = Versioned {
+            version: schema::LIMITS.current(),
+            data: self,
+        };
+        serde_json::to_writer_pretty(w, &versioned)
+    }
+
+    pub fn to_json_file<P>(&self, path: P) -> Result<(), Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+    {
+        let mut json = Vec::new();
+        self.to_json_writer(&mut json)?;
+        atomic_file::write(path, &json)?;
+
+        Ok(())
+    }
+
+    pub fn empty() -> Limits {
+        Limits {
+            limits: BTreeMap::new(),
+        }
+    }
+
+    // a budget without a limits file simply has no limits yet. for reading
+    // only: a file written by an older version is upgraded in memory
+    pub fn from_json_file_or_empty<P>(path: P) -> Result<Self, Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Limits::empty());
+        }
+
+        let mut value = schema::read(path)?;
+        schema::LIMITS.upgrade_for_reading(path, &mut value)?;
+
+        return Self::from_value(path, value);
+    }
+
+    // for changing the file, under the data lock: upgrades a file written by
+    // an older version and writes it back
+    pub fn upgrade_json_file<P>(path: P) -> Result<(), Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let mut value = schema::read(path)?;
+        if schema::LIMITS.migrate(path, &mut value)? {
+            Self::from_value(path, value)?.to_json_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    fn from_value(path: &Path, value: serde_json::Value) -> Result<Self, Box<dyn Error>> {
+        return serde_json::from_value(value)
+            .map_err(|e| format!("{} is invalid: {}", path.display(), e).into());
+    }
+
+    // the limited descendants of tag that have no limited tag between them
+    // and tag, i.e. the limits that divide up tag's limit
+    pub fn children_of(&self, tag: &Tag) -> Vec<(&Tag, Money)> {
+        let tag = tag.as_slice();
+
+        self.limits
+            .iter()
+            .filter(|(child, _)| tag.is_ancestor_of(child.as_slice()))
+            .filter(|(child, _)| {
+                !self.limits.keys().any(|between| {
+                    tag.is_ancestor_of(between.as_slice())
+                        && between.as_slice().is_ancestor_of(child.as_slice())
+                })
+            })
+            .map(|(child, amount)| (child, *amount))
+            .collect()
+    }
+
+    pub fn children_total(&self, tag: &Tag) -> Result<Option<Money>, Box<dyn Error>> {
+        let children = self.children_of(tag);
+        if children.is_empty() {
+            return Ok(None);
+        }
+
+        let mut total = Money::from_cents(0);
+        for (_, amount) in children {
+            total = total
+                .checked_add(amount)
+                .ok_or_else(|| format!("the limits of {}'s children add up to too much", tag))?;
+        }
+
+        return Ok(Some(total));
+    }
+
+    // how the limits divide up their parents' limits, for every limited tag
+    // and every parent of one, in order
+    pub fn check(&self) -> Result<Vec<LimitIssue>, Box<dyn Error>> {
+        let tags: BTreeSet<TagSlice> = self
+            .limits
+            .keys()
+            .flat_map(|tag| iter::once(tag.as_slice()).chain(tag.as_slice().parents()))
+            .collect();
+
+        let mut issues = Vec::new();
+        for tag in tags {
+            let children = self.children_total(&tag.to_tag())?;
+
+            match (self.limits.get(&tag.to_tag()).copied(), children) {
+                (Some(limit), Some(children)) if children > limit => {
+                    issues.push(LimitIssue::OverAllocated {
+                        tag: tag.to_tag(),
+                        limit,
+                        children,
+                    })
+                }
+                (Some(limit), Some(children)) if children < limit => {
+                    if let Some(remainder) = limit.checked_sub(children) {
+                        issues.push(LimitIssue::Unallocated {
+                            tag: tag.to_tag(),
+                            remainder,
+                        })
+                    }
+                }
+                (None, Some(children)) if tag.depth() > 0 => {
+                    issues.push(LimitIssue::MissingLimit {
+                        tag: tag.to_tag(),
+                        children,
+                    })
+                }
+                _ => {}
+            }
+        }
+
+        return Ok(issues);
+    }
+
+    // gives every parent without a limit the total of its children's limits,
+    // deepest first so that derived limits add up into their own parents;
+    // the root is left alone
+    pub fn derive_parents(&mut self) -> Result<Vec<(Tag, Money)>, Box<dyn Error>> {
+        let mut parents: Vec<Tag> = self
+            .limits
+            .keys()
+            .flat_map(|tag| tag.as_slice().parents())
+            .filter(|parent| parent.depth() > 0)
+            .map(|parent| parent.to_tag())
+            .filter(|parent| !self.limits.contains_key(parent))
+            .collect();
+        parents.sort_by(|a, b| {
+            let depth = b.as_slice().depth().cmp(&a.as_slice().depth());
+            depth.then(a.cmp(b))
+        });
+        parents.dedup();
+
+        let mut derived = Vec::new();
+        for parent in parents {
+            if self.limits.contains_key(&parent) {
+                continue;
+            }
+
+            if let Some(total) = self.children_total(&parent)? {
+                self.limits.insert(parent.clone(), total);
+                derived.push((parent, total));
+            }
+        }
+
+        return Ok(derived);
+    }
+}
+
+impl Display for LimitIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LimitIssue::OverAllocated {
+                tag,
+                limit,
+                children,
+            } => write!(
+                f,
+                "{}'s children have {} in limits, more than its limit of {}",
+                tag, children, limit
+            ),
+            LimitIssue::MissingLimit { tag, children } => write!(
+                f,
+                "{} has no limit, but its children have {} in limits",
+                tag, children
+            ),
+            LimitIssue::Unallocated { tag, remainder } => write!(
+                f,
+                "{} has {} of its limit not given to any child",
+                tag, remainder
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scratch::ScratchDir;
+    use std::str::FromStr;
+
+    fn limits(entries: &[(&str, i64)]) -> Limits {
+        Limits {
+            limits: entries
+                .iter()
+                .map(|(tag, cents)| (Tag::from_str(tag).unwrap(), Money::from_cents(*cents)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_children_skip_unlimited_tags_but_not_limited_ones() {
+        let limits = limits(&[
+            ("food", 30_000),
+            ("food.fast-food", 20_000),
+            ("food.fast-food.coffee", 5_000),
+            ("food.home.groceries", 25_000),
+        ]);
+        let food = Tag::from_str("food").unwrap();
+
+        let children: Vec<&Tag> = limits
+            .children_of(&food)
+            .into_iter()
+            .map(|(t, _)| t)
+            .collect();
+        assert_eq!(
+            children,
+            vec![
+                &Tag::from_str("food.fast-food").unwrap(),
+                &Tag::from_str("food.home.groceries").unwrap()
+            ]
+        );
+        assert_eq!(
+            limits.children_total(&food).unwrap(),
+            Some(Money::from_cents(45_000))
+        );
+    }
+
+    #[test]
+    fn test_derive_parents() {
+        let mut limits = limits(&[
+            ("food.home.groceries", 25_000),
+            ("food.fast-food", 5_000),
+            ("housing", 300_000),
+            ("housing.rent", 250_000),
+        ]);
+
+        let derived = limits.derive_parents().unwrap();
+
+        assert_eq!(
+            derived,
+            vec![
+                (
+                    Tag::from_str("food.home").unwrap(),
+                    Money::from_cents(25_000)
+                ),
+                (Tag::from_str("food").unwrap(), Money::from_cents(30_000)),
+            ]
+        );
+        assert_eq!(
+            limits.limits[&Tag::from_str("housing").unwrap()],
+            Money::from_cents(300_000)
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let limits = limits(&[("housing.rent", 300_000), ("food", 30_000)]);
+        let mut json = Vec::new();
+        limits.to_json_writer(&mut json).unwrap();
+        assert_eq!(Limits::from_json_reader(&json[..]).unwrap(), limits);
+    }
+
+    #[test]
+    fn test_saved_file_is_current_version() {
+        let directory = ScratchDir::new("limits");
+        let path = directory.join("limits.json");
+
+        let limits = limits(&[("housing.rent", 300_000), ("food", 30_000)]);
+        limits.to_json_file(&path).unwrap();
+        let value = schema::read(&path).unwrap();
+        assert_eq!(
+            schema::LIMITS.version_of(&value).unwrap(),
+            schema::LIMITS.current()
+        );
+        assert_eq!(value["version"], schema::LIMITS.current());
+        assert_eq!(Limits::from_json_file_or_empty(&path).unwrap(), limits);
+    }
+
+    #[test]
+    fn test_over_allocated() {
+        let limits = limits(&[
+            ("food", 30_000),
+            ("food.groceries", 30_000),
+            ("food.fast-food", 20_000),
+        ]);
+
+        assert_eq!(
+            limits.check().unwrap(),
+            vec![LimitIssue::OverAllocated {
+                tag: Tag::from_str("food").unwrap(),
+                limit: Money::from_cents(30_000),
+                children: Money::from_cents(50_000),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_missing_and_unallocated() {
+        let limits = limits(&[("food", 30_000), ("food.home.groceries", 20_000)]);
+
+        assert_eq!(
+            limits.check().unwrap(),
+            vec![
+                LimitIssue::Unallocated {
+                    tag: Tag::from_str("food").unwrap(),
+                    remainder: Money::from_cents(10_000),
+                },
+                LimitIssue::MissingLimit {
+                    tag: Tag::from_str("food.home").unwrap(),
+                    children: Money::from_cents(20_000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_children_overflow() {
+        let limits = limits(&[
+            ("food.groceries", i64::MAX),
+            ("food.fast-food", 1),
+            ("food.tea", 1),
+        ]);
+
+        assert!(limits.check().is_err());
+    }
 }
@@ -0,0 +1,176 @@
+use crate::settings;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, Write};
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+// an exclusive advisory lock on the data directory, held for the whole
+// read-modify-write of a command. the lock file holds the pid of the process
+// holding the lock, and is emptied again when the lock is released, so a pid
+// found in it on acquiring means the previous holder died while holding it
+#[derive(Debug)]
+pub struct DataLock {
+    file: File,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum LockError {
+    #[error("could not open the lock file: {0}")]
+    Io(#[from] io::Error),
+    #[error("gave up after {} seconds waiting for {}", .0.as_secs(), holder(.1))]
+    TimedOut(Duration, Option<u32>),
+}
+
+fn holder(pid: &Option<u32>) -> String {
+    match pid {
+        Some(pid) if is_running(*pid) == Some(false) => format!(
+            "process {}, which is no longer running; the lock may be stale",
+            pid
+        ),
+        Some(pid) => format!("process {} to release the lock", pid),
+        None => "another process to release the lock".to_string(),
+    }
+}
+
+impl DataLock {
+    // waits as long as the budget's lock.timeout_secs setting says
+    pub fn acquire<P>(path: P) -> Result<DataLock, LockError>
+    where
+        P: AsRef<Path>,
+    {
+        let timeout = Duration::from_secs(settings::get().lock.timeout_secs);
+
+        return DataLock::acquire_with_timeout(path, timeout);
+    }
+
+    pub fn acquire_with_timeout<P>(path: P, timeout: Duration) -> Result<DataLock, LockError>
+    where
+        P: AsRef<Path>,
+    {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let start = Instant::now();
+        let mut waiting = false;
+        while let Err(e) = file.try_lock() {
+            match e {
+                std::fs::TryLockError::WouldBlock => {}
+                std::fs::TryLockError::Error(e) => return Err(e.into()),
+            }
+
+            let pid = read_pid(&mut file);
+            if start.elapsed() >= timeout {
+                return Err(LockError::TimedOut(timeout, pid));
+            }
+
+            if !waiting {
+                match pid {
+                    Some(pid) => eprintln!("waiting for process {} to release the lock...", pid),
+                    None => eprintln!("waiting for another process to release the lock..."),
+                }
+                waiting = true;
+            }
+            thread::sleep(RETRY_INTERVAL);
+        }
+
+        if let Some(pid) = read_pid(&mut file) {
+            eprintln!(
+                "warning: cleared a stale lock left by process {}; check that its last change was saved",
+                pid
+            );
+        }
+
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{}", process::id())?;
+        file.sync_all()?;
+
+        return Ok(DataLock { file });
+    }
+}
+
+impl Drop for DataLock {
+    // the lock itself is released when the file is closed
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+    }
+}
+
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut contents = String::new();
+    file.rewind().ok()?;
+    file.read_to_string(&mut contents).ok()?;
+
+    return contents.trim().parse().ok();
+}
+
+// None when there is no way to tell
+#[cfg(target_os = "linux")]
+fn is_running(pid: u32) -> Option<bool> {
+    Some(Path::new("/proc").join(pid.to_string()).exists())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_running(_pid: u32) -> Option<bool> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scratch::ScratchDir;
+    use std::fs;
+
+    #[test]
+    fn test_acquire_and_release() {
+        let directory = ScratchDir::new("lock-release");
+        let path = directory.join("budget.lock");
+
+        let lock = DataLock::acquire_with_timeout(&path, Duration::ZERO).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            process::id().to_string()
+        );
+
+        drop(lock);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        assert!(DataLock::acquire_with_timeout(&path, Duration::ZERO).is_ok());
+    }
+
+    #[test]
+    fn test_second_acquire_times_out() {
+        let directory = ScratchDir::new("lock-contended");
+        let path = directory.join("budget.lock");
+
+        let _held = DataLock::acquire_with_timeout(&path, Duration::ZERO).unwrap();
+        match DataLock::acquire_with_timeout(&path, Duration::from_millis(200)) {
+            Err(LockError::TimedOut(_, pid)) => assert_eq!(pid, Some(process::id())),
+            other => panic!("expected the second acquire to time out, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_second_acquire_waits_for_release() {
+        let directory = ScratchDir::new("lock-waits");
+        let path = directory.join("budget.lock");
+
+        let held = DataLock::acquire_with_timeout(&path, Duration::ZERO).unwrap();
+        let releaser = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(300));
+            drop(held);
+        });
+
+        let start = Instant::now();
+        assert!(DataLock::acquire_with_timeout(&path, Duration::from_secs(10)).is_ok());
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        releaser.join().unwrap();
+    }
+}
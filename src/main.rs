@@ -1,32 +1,75 @@
 #![feature(file_buffered)]
 
+mod accounting;
+mod archive;
 mod arg_parsers;
+mod atomic_file;
 mod colors;
 mod commands;
+mod csv;
 mod date;
+mod filter;
+mod forecast;
+mod import;
+mod journal;
 mod limits;
+mod lock;
 mod money;
 mod number_parsers;
+mod paths;
+mod percent;
+mod period;
+mod profiles;
+mod progress;
+mod query;
+mod schema;
+#[cfg(test)]
+mod scratch;
+mod settings;
+mod storage;
 mod tag;
 mod transaction;
 mod transaction_tree;
-use crate::date::Date;
-use clap::{Parser, Subcommand};
+use crate::date::{Date, Month};
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use commands::list::SortKey;
+use commands::query::Format;
+use filter::{CommentMatch, Filter};
 use money::Money;
+use paths::{Environment, Paths};
+use period::Period;
+use profiles::Profiles;
+use regex::Regex;
+use settings::Settings;
 use std::error::Error;
 use std::path::PathBuf;
+use std::process;
+use storage::Format as StorageFormat;
 use tag::Tag;
+use transaction::{Selector, TransactionId};
 
 #[derive(Parser, Debug)]
 #[command(author, version)]
 struct Command {
+    /// the budget's directory; by default $BUDGET_DIR, or else the nearest
+    /// one at or above the current directory
+    #[arg(long, global = true)]
+    data_dir: Option<PathBuf>,
+    /// use the named profile's budget, by default $BUDGET_PROFILE; not
+    /// while $BUDGET_DIR is set; see budget profile list
+    #[arg(long, global = true)]
+    profile: Option<String>,
     #[command(subcommand)]
     subcommand: Subcommands,
 }
 
 #[derive(Subcommand, Debug)]
 enum Subcommands {
-    Init,
+    Init {
+        /// how the transactions are stored; see budget storage convert
+        #[arg(long, value_enum, default_value = "json")]
+        format: StorageFormat,
+    },
     Open,
     Close,
     Add {
@@ -36,16 +79,103 @@ enum Subcommands {
         date: Option<Date>,
         #[arg(long, short)]
         comment: Option<String>,
+        /// refuse to add a transaction that puts a tag over its limit or ahead of pace
+        #[arg(long)]
+        strict: bool,
     },
     Remove {
-        tag: Tag,
-        amount: Money,
-        #[arg(long, short)]
-        date: Option<Date>,
-        #[arg(long, short)]
-        comment: Option<String>,
+        #[command(flatten)]
+        selector: SelectorArgs,
+    },
+    /// select a transaction like remove does, then change it with the --set-* options
+    #[command(group(ArgGroup::new("changes").required(true).multiple(true)))]
+    Edit {
+        #[command(flatten)]
+        selector: SelectorArgs,
+        #[arg(long, group = "changes")]
+        set_date: Option<Date>,
+        #[arg(long, group = "changes")]
+        set_amount: Option<Money>,
+        #[arg(long, group = "changes")]
+        set_tag: Option<Tag>,
+        #[arg(long, group = "changes")]
+        set_comment: Option<String>,
+    },
+    Status {
+        /// show an archived month, e.g. 2025-08
+        #[arg(long, short, conflicts_with = "from")]
+        month: Option<Month>,
+        /// show every day from this date through --to, with limits scaled to fit
+        #[arg(long, requires = "to")]
+        from: Option<Date>,
+        #[arg(long, requires = "from")]
+        to: Option<Date>,
+        /// show every profile's status, one after the other
+        #[arg(long)]
+        all_profiles: bool,
+    },
+    List {
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[arg(long, value_enum, default_value = "date")]
+        sort: SortKey,
+        #[arg(long)]
+        reverse: bool,
+        /// show at most this many transactions
+        #[arg(long)]
+        limit: Option<usize>,
+        /// skip this many transactions before showing any
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+    },
+    /// e.g. 'tag:food.** and amount > 20.00 and comment ~ "uber" group by month sum avg'
+    Query {
+        query: String,
+        #[arg(long, value_enum, default_value = "table")]
+        format: Format,
+    },
+    Limit {
+        #[command(subcommand)]
+        subcommand: LimitSubcommands,
+    },
+    Profile {
+        #[command(subcommand)]
+        subcommand: ProfileSubcommands,
+    },
+    Storage {
+        #[command(subcommand)]
+        subcommand: StorageSubcommands,
+    },
+    /// show or change the settings in budget.toml
+    Config {
+        #[command(subcommand)]
+        subcommand: ConfigSubcommands,
+    },
+    /// write transactions out for other programs
+    Export {
+        #[command(subcommand)]
+        subcommand: ExportSubcommands,
+    },
+    /// add transactions from other programs' files
+    Import {
+        #[command(subcommand)]
+        subcommand: ImportSubcommands,
+    },
+    /// upgrade data files written by older versions
+    Migrate {
+        /// only list the upgrades, failing if there are any
+        #[arg(long)]
+        check: bool,
+    },
+    /// revert the most recent change to the transactions or limits
+    Undo,
+    /// apply the most recently undone change again
+    Redo,
+    /// show the most recent changes, newest first
+    Log {
+        #[arg(long, short = 'n', default_value_t = 10)]
+        count: usize,
     },
-    Status,
     Reconcile {
         file: PathBuf,
     },
@@ -54,27 +184,348 @@ enum Subcommands {
     },
 }
 
+// how remove and edit pick out a transaction: by its id, or by its tag and
+// amount narrowed down with the date and comment
+#[derive(Args, Debug)]
+struct SelectorArgs {
+    #[arg(required_unless_present = "id")]
+    tag: Option<Tag>,
+    #[arg(required_unless_present = "id")]
+    amount: Option<Money>,
+    #[arg(long, short)]
+    date: Option<Date>,
+    #[arg(long, short)]
+    comment: Option<String>,
+    /// the id shown by list
+    #[arg(long)]
+    id: Option<TransactionId>,
+}
+
+impl SelectorArgs {
+    fn into_selector(self) -> Selector {
+        Selector {
+            id: self.id,
+            tag: self.tag,
+            amount: self.amount,
+            date: self.date,
+            comment: self.comment,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+struct FilterArgs {
+    #[arg(long, short)]
+    tag: Option<Tag>,
+    /// also match the descendants of --tag
+    #[arg(long, requires = "tag")]
+    subtree: bool,
+    #[arg(long)]
+    from: Option<Date>,
+    #[arg(long)]
+    to: Option<Date>,
+    #[arg(long)]
+    min: Option<Money>,
+    #[arg(long)]
+    max: Option<Money>,
+    /// match comments containing this text
+    #[arg(long, short)]
+    comment: Option<String>,
+    /// match comments against this regular expression
+    #[arg(long, conflicts_with = "comment")]
+    regex: Option<Regex>,
+    /// the id shown by list; it picks among the current transactions only
+    #[arg(long)]
+    id: Option<TransactionId>,
+}
+
+impl FilterArgs {
+    fn into_filter(self) -> Filter {
+        let comment = match (self.comment, self.regex) {
+            (Some(s), _) => Some(CommentMatch::Contains(s)),
+            (None, Some(r)) => Some(CommentMatch::Regex(r)),
+            (None, None) => None,
+        };
+
+        Filter {
+            tag: self.tag,
+            subtree: self.subtree,
+            from: self.from,
+            to: self.to,
+            min: self.min,
+            max: self.max,
+            comment,
+            id: self.id,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum LimitSubcommands {
+    Set {
+        tag: Tag,
+        amount: Money,
+    },
+    Unset {
+        tag: Tag,
+    },
+    List,
+    Show {
+        tag: Tag,
+    },
+    /// report children that over-allocate their parent and parents missing limits
+    Check,
+    /// give parents without a limit the total of their children's limits
+    Derive,
+}
+
+#[derive(Subcommand, Debug)]
+enum ProfileSubcommands {
+    /// show every profile; the default one is marked with *
+    List,
+    /// add a profile, setting up a new budget unless the directory holds one
+    Create {
+        name: String,
+        /// by default, a directory under $XDG_DATA_HOME/budget
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+    /// make a profile the default
+    Switch { name: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigSubcommands {
+    /// show a setting, e.g. display.currency, or every setting
+    Get {
+        key: Option<String>,
+    },
+    Set {
+        key: String,
+        value: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ExportSubcommands {
+    /// the matching transactions, archived ones included, as CSV
+    Csv {
+        #[command(flatten)]
+        filter: FilterArgs,
+        /// the spent, limit and remaining amount of each tag instead
+        #[arg(long)]
+        summary: bool,
+    },
+    /// the matching transactions as ledger or hledger entries; see the
+    /// export settings in budget config get
+    Ledger {
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+    /// the matching transactions as beancount entries
+    Beancount {
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ImportSubcommands {
+    /// the expense postings of a ledger, hledger or beancount journal
+    Ledger {
+        file: PathBuf,
+        /// take postings to accounts under this one, e.g. Expenses:; by
+        /// default import.account_prefix
+        #[arg(long)]
+        prefix: Option<String>,
+        #[command(flatten)]
+        options: ImportArgs,
+    },
+    /// the transactions of an OFX or QFX bank statement, tagged by the
+    /// import.rules in budget.toml
+    Ofx {
+        file: PathBuf,
+        /// leave out money that came into the account, such as pay
+        #[arg(long)]
+        skip_credits: bool,
+        #[command(flatten)]
+        options: ImportArgs,
+    },
+}
+
+#[derive(Args, Debug)]
+struct ImportArgs {
+    /// only show what would be imported
+    #[arg(long)]
+    dry_run: bool,
+    /// import without asking first
+    #[arg(long, short)]
+    yes: bool,
+}
+
+impl ImportArgs {
+    fn into_options(self) -> commands::import::Options {
+        commands::import::Options {
+            dry_run: self.dry_run,
+            yes: self.yes,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum StorageSubcommands {
+    /// move the transactions to transactions.json or to transactions.jsonl
+    Convert {
+        #[arg(value_enum)]
+        format: StorageFormat,
+    },
+    /// rewrite transactions.jsonl without superseded records
+    Compact,
+}
+
 fn main() -> () {
     let command = Command::parse();
 
-    let result: Result<(), Box<dyn Error>> = match command.subcommand {
-        Subcommands::Init => commands::init::init(),
+    if let Err(err) = run(command) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(command: Command) -> Result<(), Box<dyn Error>> {
+    // these work without a budget to operate on
+    let subcommand = match command.subcommand {
+        Subcommands::Init { format } => {
+            return commands::init::init(
+                &Paths::for_init(command.data_dir, Environment::current()?),
+                format,
+            );
+        }
+        Subcommands::Profile { subcommand } => {
+            return match subcommand {
+                ProfileSubcommands::List => commands::profile::list(),
+                ProfileSubcommands::Create { name, dir } => commands::profile::create(name, dir),
+                ProfileSubcommands::Switch { name } => commands::profile::switch(name),
+            };
+        }
+        Subcommands::Status {
+            month,
+            from,
+            to,
+            all_profiles: true,
+        } => return commands::status::all_profiles(month, range(from, to)?),
+        subcommand => subcommand,
+    };
+
+    let paths = Paths::find(
+        command.data_dir,
+        command.profile,
+        Environment::current()?,
+        Profiles::load,
+    )?;
+    settings::install(Settings::from_toml_file_or_default(paths.settings())?);
+
+    match subcommand {
+        Subcommands::Init { .. } | Subcommands::Profile { .. } => {
+            unreachable!("these run before a budget is looked for")
+        }
         Subcommands::Add {
             tag,
             amount,
             date,
             comment,
-        } => commands::add::add(tag, amount, date.unwrap_or(Date::today()), comment),
-        Subcommands::Remove {
+            strict,
+        } => commands::add::add(
+            &paths,
             tag,
             amount,
-            date,
+            date.unwrap_or(Date::today()),
             comment,
-        } => commands::remove::remove(tag, amount, date, comment),
+            strict,
+        ),
+        Subcommands::Remove { selector } => {
+            commands::remove::remove(&paths, selector.into_selector())
+        }
+        Subcommands::Edit {
+            selector,
+            set_date,
+            set_amount,
+            set_tag,
+            set_comment,
+        } => commands::edit::edit(
+            &paths,
+            selector.into_selector(),
+            commands::edit::Changes {
+                date: set_date,
+                amount: set_amount,
+                tag: set_tag,
+                comment: set_comment,
+            },
+        ),
+        Subcommands::Status {
+            month, from, to, ..
+        } => commands::status::status(&paths, month, range(from, to)?),
+        Subcommands::List {
+            filter,
+            sort,
+            reverse,
+            limit,
+            offset,
+        } => commands::list::list(&paths, filter.into_filter(), sort, reverse, limit, offset),
+        Subcommands::Query { query, format } => commands::query::query(&paths, &query, format),
+        Subcommands::Limit { subcommand } => match subcommand {
+            LimitSubcommands::Set { tag, amount } => commands::limit::set(&paths, tag, amount),
+            LimitSubcommands::Unset { tag } => commands::limit::unset(&paths, tag),
+            LimitSubcommands::List => commands::limit::list(&paths),
+            LimitSubcommands::Show { tag } => commands::limit::show(&paths, tag),
+            LimitSubcommands::Check => commands::limit::check(&paths),
+            LimitSubcommands::Derive => commands::limit::derive(&paths),
+        },
+        Subcommands::Storage { subcommand } => match subcommand {
+            StorageSubcommands::Convert { format } => commands::storage::convert(&paths, format),
+            StorageSubcommands::Compact => commands::storage::compact(&paths),
+        },
+        Subcommands::Config { subcommand } => match subcommand {
+            ConfigSubcommands::Get { key } => commands::config::get(&paths, key),
+            ConfigSubcommands::Set { key, value } => commands::config::set(&paths, &key, &value),
+        },
+        Subcommands::Export { subcommand } => match subcommand {
+            ExportSubcommands::Csv { filter, summary } => {
+                commands::export::csv(&paths, filter.into_filter(), summary)
+            }
+            ExportSubcommands::Ledger { filter } => {
+                commands::export::ledger(&paths, filter.into_filter())
+            }
+            ExportSubcommands::Beancount { filter } => {
+                commands::export::beancount(&paths, filter.into_filter())
+            }
+        },
+        Subcommands::Import { subcommand } => match subcommand {
+            ImportSubcommands::Ledger {
+                file,
+                prefix,
+                options,
+            } => commands::import::ledger(&paths, &file, prefix, options.into_options()),
+            ImportSubcommands::Ofx {
+                file,
+                skip_credits,
+                options,
+            } => commands::import::ofx(&paths, &file, skip_credits, options.into_options()),
+        },
+        Subcommands::Migrate { check } => commands::migrate::migrate(&paths, check),
+        Subcommands::Undo => commands::history::undo(&paths),
+        Subcommands::Redo => commands::history::redo(&paths),
+        Subcommands::Log { count } => commands::history::log(&paths, count),
         _ => panic!("encountered unimplemented command"),
-    };
+    }
+}
 
-    if let Err(err) = result {
-        println!("error: {}", err);
+fn range(from: Option<Date>, to: Option<Date>) -> Result<Option<Period>, Box<dyn Error>> {
+    match (from, to) {
+        (Some(from), Some(to)) => match Period::new(from, to) {
+            Some(range) => Ok(Some(range)),
+            None => Err("--from must not be after --to".into()),
+        },
+        _ => Ok(None),
     }
 }
@@ -1,4 +1,5 @@
-use crate::date::Date;
+use crate::percent::Percent;
+use crate::settings;
 use crate::{colors, number_parsers};
 use serde::{Deserialize, Deserializer, de::Error};
 use serde::{Serialize, Serializer};
@@ -35,20 +36,37 @@ impl Money {
         Money { cents }
     }
 
-    pub fn checked_div(&self, other: Money) -> Option<i64> {
-        self.cents.checked_div(other.cents)
+    pub fn checked_add(&self, other: Money) -> Option<Money> {
+        Some(Money {
+            cents: self.cents.checked_add(other.cents)?,
+        })
     }
 
-    pub fn left_to_date_in_month(date: Date, limit: Money, spent: Money) -> Option<Money> {
+    pub fn checked_sub(&self, other: Money) -> Option<Money> {
+        Some(Money {
+            cents: self.cents.checked_sub(other.cents)?,
+        })
+    }
+
+    // multiplies by numerator / denominator, rounding toward zero; the
+    // intermediate product is widened so that prorating large limits by a
+    // number of days cannot overflow
+    pub fn checked_scale(&self, numerator: i64, denominator: i64) -> Option<Money> {
+        let product = i128::from(self.cents).checked_mul(numerator.into())?;
+        let quotient = product.checked_div(denominator.into())?;
+
         Some(Money {
-            cents: {
-                let cents_per_day = limit.cents.checked_div(date.days_in_month().into())?;
-                let cents_accumulated = cents_per_day.checked_mul(date.day().into())?;
-                let net_available = cents_accumulated.checked_sub(spent.cents)?;
-                net_available
-            },
+            cents: quotient.try_into().ok()?,
         })
     }
+
+    pub fn percent_of(&self, whole: Money) -> Option<Percent> {
+        let basis_points = i128::from(self.cents)
+            .checked_mul(10_000)?
+            .checked_div(whole.cents.into())?;
+
+        Some(Percent::from_basis_points(basis_points.try_into().ok()?))
+    }
 }
 
 impl FromStr for Money {
@@ -78,25 +96,73 @@ impl FromStr for Money {
     }
 }
 
+impl Money {
+    // reads amounts the way other programs write them: with a sign, and with
+    // up to two decimals or none at all, e.g. -45.1 or 12
+    pub fn from_decimal(s: &str) -> Result<Money, MoneyError> {
+        let (negative, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (dollars, cents) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+
+        if !dollars.chars().all(|c| c.is_ascii_digit()) || (dollars.is_empty() && cents.is_empty())
+        {
+            return Err(MoneyError::InvalidDollars);
+        }
+        if cents.len() > 2 || !cents.chars().all(|c| c.is_ascii_digit()) {
+            return Err(MoneyError::InvalidCents);
+        }
+
+        let dollars: i64 = match dollars {
+            "" => 0,
+            _ => dollars.parse().map_err(|_| MoneyError::Overflow)?,
+        };
+        let cents: i64 = format!("{:0<2}", cents)
+            .parse()
+            .map_err(|_| MoneyError::InvalidCents)?;
+
+        let total_cents = dollars
+            .checked_mul(100)
+            .and_then(|d| d.checked_add(cents))
+            .ok_or(MoneyError::Overflow)?;
+
+        return Ok(Money {
+            cents: if negative { -total_cents } else { total_cents },
+        });
+    }
+
+    // the amount without colors or a currency symbol, e.g. for files
+    pub fn to_plain_string(self) -> String {
+        let sign = if self.cents < 0 { "-" } else { "" };
+        let cents: u64 = self.cents.unsigned_abs() % 100;
+        let dollars: u64 = self.cents.unsigned_abs() / 100;
+        format!("{}{}.{:02}", sign, dollars, cents)
+    }
+}
+
 impl fmt::Display for Money {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let currency = &settings::get().display.currency;
         if self.cents >= 0 {
             write!(
                 f,
-                "{}${}.{:02}{}",
-                colors::GREEN,
+                "{}{}{}.{:02}{}",
+                colors::green(),
+                currency,
                 self.cents / 100,
                 self.cents % 100,
-                colors::RESET
+                colors::reset()
             )
         } else {
             write!(
                 f,
-                "{}−${}.{:02}{}",
-                colors::RED,
+                "{}−{}{}.{:02}{}",
+                colors::red(),
+                currency,
                 -self.cents / 100,
                 -self.cents % 100,
-                colors::RESET
+                colors::reset()
             )
         }
     }
@@ -187,6 +253,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_scale() {
+        let limit = Money::from_cents(30_000);
+        assert_eq!(limit.checked_scale(10, 30), Some(Money::from_cents(10_000)));
+        assert_eq!(
+            Money::from_cents(100).checked_scale(1, 3),
+            Some(Money::from_cents(33))
+        );
+        assert_eq!(limit.checked_scale(1, 0), None);
+        assert_eq!(
+            Money::from_cents(i64::MAX).checked_scale(30, 31),
+            Some(Money::from_cents(8_925_843_906_633_654_006))
+        );
+    }
+
+    #[test]
+    fn test_percent_of() {
+        let spent = Money::from_cents(4_512);
+        let limit = Money::from_cents(10_000);
+        assert_eq!(
+            spent.percent_of(limit),
+            Some(Percent::from_basis_points(4_512))
+        );
+        assert_eq!(spent.percent_of(Money::from_cents(0)), None);
+    }
+
+    #[test]
+    fn test_serialize_pads_cents() {
+        assert_eq!(
+            serde_json::to_string(&Money::from_cents(150_000)).unwrap(),
+            "\"1500.00\""
+        );
+        assert_eq!(
+            serde_json::to_string(&Money::from_cents(105)).unwrap(),
+            "\"1.05\""
+        );
+    }
+
+    #[test]
+    fn test_plain_string_of_negative_amounts() {
+        assert_eq!(Money::from_cents(-150).to_plain_string(), "-1.50");
+        assert_eq!(Money::from_cents(-5).to_plain_string(), "-0.05");
+    }
+
+    #[test]
+    fn test_negative_amounts_round_trip() {
+        for cents in [-150, -5, -100_000] {
+            let json = serde_json::to_string(&Money::from_cents(cents)).unwrap();
+            let money: Money = serde_json::from_str(&json).unwrap();
+            assert_eq!(money, Money::from_cents(cents));
+        }
+        assert!(serde_json::from_str::<Money>("\"--1.00\"").is_err());
+    }
+
+    #[test]
+    fn test_from_decimal() {
+        assert_eq!(Money::from_decimal("-45.1"), Ok(Money::from_cents(-4_510)));
+        assert_eq!(Money::from_decimal("12"), Ok(Money::from_cents(1_200)));
+        assert_eq!(Money::from_decimal("+.5"), Ok(Money::from_cents(50)));
+        assert_eq!(Money::from_decimal("-0.05"), Ok(Money::from_cents(-5)));
+        assert_eq!(Money::from_decimal("1.234"), Err(MoneyError::InvalidCents));
+        assert_eq!(Money::from_decimal("--1"), Err(MoneyError::InvalidDollars));
+        assert_eq!(Money::from_decimal("."), Err(MoneyError::InvalidDollars));
+    }
+
     #[test]
     fn test_pluses() {
         assert_eq!(
@@ -205,8 +336,15 @@ impl<'de> Deserialize<'de> for Money {
     where
         D: Deserializer<'de>,
     {
+        // refunds and other money coming back are written with a minus sign,
+        // which is not accepted on the command line
         let s = String::deserialize(deserializer)?;
-        Money::from_str(&s).map_err(D::Error::custom)
+        match s.strip_prefix('-') {
+            Some(unsigned) => Money::from_str(unsigned)
+                .map(|m| Money { cents: -m.cents })
+                .map_err(D::Error::custom),
+            None => Money::from_str(&s).map_err(D::Error::custom),
+        }
     }
 }
 
@@ -215,9 +353,6 @@ impl Serialize for Money {
     where
         S: Serializer,
     {
-        let cents: i64 = self.cents % 100;
-        let dollars: i64 = self.cents / 100;
-        serializer
-            .serialize_str(format!("{}.{:02}", dollars.to_string(), cents.to_string()).as_str())
+        serializer.serialize_str(self.to_plain_string().as_str())
     }
 }
@@ -0,0 +1,243 @@
+use crate::profiles::Profiles;
+use std::env;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+// where every data file of a budget lives, relative to its root directory
+#[derive(Debug, Clone)]
+pub struct Paths {
+    root: PathBuf,
+}
+
+// what Paths::find looks at besides the command line
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    // $BUDGET_DIR
+    pub budget_dir: Option<PathBuf>,
+    // $BUDGET_PROFILE
+    pub budget_profile: Option<String>,
+    pub cwd: PathBuf,
+}
+
+impl Environment {
+    pub fn current() -> Result<Environment, Box<dyn Error>> {
+        return Ok(Environment {
+            budget_dir: env::var_os("BUDGET_DIR").map(PathBuf::from),
+            budget_profile: env::var("BUDGET_PROFILE").ok(),
+            cwd: env::current_dir()?,
+        });
+    }
+}
+
+impl Paths {
+    pub fn new<P>(root: P) -> Paths
+    where
+        P: Into<PathBuf>,
+    {
+        Paths { root: root.into() }
+    }
+
+    // the first of: the directory given with --data-dir, the one in
+    // BUDGET_DIR, the directory of the profile given with --profile, the
+    // one of the profile in BUDGET_PROFILE, the nearest directory at or
+    // above the current one that holds a budget, the way git finds .git, and
+    // the default profile's. passing both options is refused, and so is
+    // --profile while BUDGET_DIR is set, rather than quietly using a budget
+    // other than the one asked for. the profiles are only loaded when needed
+    pub fn find<F>(
+        data_dir: Option<PathBuf>,
+        profile: Option<String>,
+        environment: Environment,
+        load_profiles: F,
+    ) -> Result<Paths, Box<dyn Error>>
+    where
+        F: FnOnce() -> Result<Profiles, Box<dyn Error>>,
+    {
+        if data_dir.is_some() && profile.is_some() {
+            return Err("pass either --data-dir or --profile, not both".into());
+        }
+
+        if environment.budget_dir.is_some() && profile.is_some() {
+            return Err("BUDGET_DIR is set; unset it to use --profile".into());
+        }
+
+        if let Some(root) = data_dir.or(environment.budget_dir) {
+            let paths = Paths::new(root);
+            if !paths.holds_budget() {
+                return Err(format!("{} does not hold a budget", paths.root.display()).into());
+            }
+
+            return Ok(paths);
+        }
+
+        if let Some(name) = profile.or(environment.budget_profile) {
+            return load_profiles()?.paths(&name);
+        }
+
+        for directory in environment.cwd.ancestors() {
+            let paths = Paths::new(directory);
+            if paths.holds_budget() {
+                return Ok(paths);
+            }
+        }
+
+        if let Some(paths) = load_profiles()?.default_paths() {
+            return paths;
+        }
+
+        return Err(format!(
+            "no budget found in {} or any of its parents; run budget init, or pass --data-dir or --profile",
+            environment.cwd.display()
+        )
+        .into());
+    }
+
+    // a new budget goes in the directory it is told to, or else right here
+    pub fn for_init(data_dir: Option<PathBuf>, environment: Environment) -> Paths {
+        let root = data_dir
+            .or(environment.budget_dir)
+            .unwrap_or(environment.cwd);
+
+        Paths::new(root)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn transactions(&self) -> PathBuf {
+        self.root.join("transactions.json")
+    }
+
+    // the append-only alternative to transactions.json
+    pub fn transactions_log(&self) -> PathBuf {
+        self.root.join("transactions.jsonl")
+    }
+
+    fn holds_budget(&self) -> bool {
+        self.transactions().exists() || self.transactions_log().exists()
+    }
+
+    pub fn limits(&self) -> PathBuf {
+        self.root.join("limits.json")
+    }
+
+    pub fn archive(&self) -> PathBuf {
+        self.root.join("archive")
+    }
+
+    pub fn journal(&self) -> PathBuf {
+        self.root.join("journal.json")
+    }
+
+    pub fn settings(&self) -> PathBuf {
+        self.root.join("budget.toml")
+    }
+
+    pub fn lock(&self) -> PathBuf {
+        self.root.join("budget.lock")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scratch::ScratchDir;
+    use std::collections::BTreeMap;
+    use std::fs;
+
+    // budgets a to e, with profiles for b, c and d, d being the default,
+    // and a directory f that holds none
+    fn setup(root: &Path) -> Profiles {
+        for name in ["a", "b", "c", "d", "e/x/y", "f"] {
+            fs::create_dir_all(root.join(name)).unwrap();
+        }
+        for name in ["a", "b", "c", "d", "e"] {
+            fs::write(Paths::new(root.join(name)).transactions(), "{}").unwrap();
+        }
+
+        return Profiles {
+            default: Some("d".to_string()),
+            profiles: ["b", "c", "d"]
+                .into_iter()
+                .map(|name| (name.to_string(), root.join(name)))
+                .collect::<BTreeMap<_, _>>(),
+        };
+    }
+
+    #[test]
+    fn test_find_order() {
+        let directory = ScratchDir::new("paths");
+        let root = directory.path();
+        let profiles = setup(root);
+        let everything = Environment {
+            budget_dir: Some(root.join("a")),
+            budget_profile: Some("c".to_string()),
+            cwd: root.join("e/x/y"),
+        };
+        let find = |data_dir: Option<&str>, profile: Option<&str>, environment: &Environment| {
+            Paths::find(
+                data_dir.map(|name| root.join(name)),
+                profile.map(str::to_string),
+                environment.clone(),
+                || Ok(profiles.clone()),
+            )
+            .map(|paths| paths.root().to_path_buf())
+        };
+
+        // --data-dir
+        assert_eq!(find(Some("b"), None, &everything).unwrap(), root.join("b"));
+        // BUDGET_DIR
+        assert_eq!(find(None, None, &everything).unwrap(), root.join("a"));
+        // --profile
+        let no_dir = Environment {
+            budget_dir: None,
+            ..everything.clone()
+        };
+        assert_eq!(find(None, Some("b"), &no_dir).unwrap(), root.join("b"));
+        // BUDGET_PROFILE
+        assert_eq!(find(None, None, &no_dir).unwrap(), root.join("c"));
+        // the search upward
+        let no_profile = Environment {
+            budget_profile: None,
+            ..no_dir.clone()
+        };
+        assert_eq!(find(None, None, &no_profile).unwrap(), root.join("e"));
+        // the default profile
+        let nowhere = Environment {
+            cwd: root.join("f"),
+            ..no_profile.clone()
+        };
+        assert_eq!(find(None, None, &nowhere).unwrap(), root.join("d"));
+    }
+
+    #[test]
+    fn test_find_refusals() {
+        let directory = ScratchDir::new("paths-refused");
+        let root = directory.path();
+        let profiles = setup(root);
+        let find = |data_dir: Option<&str>, profile: Option<&str>, environment: Environment| {
+            Paths::find(
+                data_dir.map(|name| root.join(name)),
+                profile.map(str::to_string),
+                environment,
+                || Ok(profiles.clone()),
+            )
+        };
+        let nowhere = Environment {
+            cwd: root.join("f"),
+            ..Environment::default()
+        };
+
+        assert!(find(Some("a"), Some("b"), nowhere.clone()).is_err());
+        let with_dir = Environment {
+            budget_dir: Some(root.join("a")),
+            ..nowhere.clone()
+        };
+        assert!(find(None, Some("b"), with_dir).is_err());
+        assert!(find(Some("f"), None, nowhere.clone()).is_err());
+        assert!(find(None, Some("z"), nowhere.clone()).is_err());
+        let no_default = Paths::find(None, None, nowhere, || Ok(Profiles::default()));
+        assert!(no_default.is_err());
+    }
+}
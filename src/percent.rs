@@ -0,0 +1,40 @@
+use std::fmt;
+
+// stored in hundredths of a percent so that it can be displayed with two
+// decimals without floating point
+#[derive(Debug, Eq, PartialEq, Clone, PartialOrd, Ord, Copy)]
+pub struct Percent {
+    basis_points: i64,
+}
+
+impl Percent {
+    pub const HUNDRED: Percent = Percent {
+        basis_points: 10_000,
+    };
+
+    pub fn from_basis_points(basis_points: i64) -> Percent {
+        Percent { basis_points }
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.basis_points < 0 { "−" } else { "" };
+        let magnitude = self.basis_points.unsigned_abs();
+
+        write!(f, "{}{}.{:02}%", sign, magnitude / 100, magnitude % 100)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Percent::from_basis_points(1234).to_string(), "12.34%");
+        assert_eq!(Percent::from_basis_points(5).to_string(), "0.05%");
+        assert_eq!(Percent::from_basis_points(-150).to_string(), "−1.50%");
+        assert_eq!(Percent::HUNDRED.to_string(), "100.00%");
+    }
+}
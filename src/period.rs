@@ -0,0 +1,145 @@
+use crate::date::{Date, Month};
+use std::fmt::{self, Display};
+
+// an inclusive range of days that a set of limits applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    start: Date,
+    end: Date,
+}
+
+impl Period {
+    pub fn new(start: Date, end: Date) -> Option<Period> {
+        if start > end {
+            return None;
+        }
+
+        return Some(Period { start, end });
+    }
+
+    pub fn month_of(date: Date) -> Period {
+        Period {
+            start: date.first_of_month(),
+            end: date.last_of_month(),
+        }
+    }
+
+    // monday through sunday
+    pub fn week_of(date: Date) -> Period {
+        let (start, end) = date.week_bounds();
+
+        return Period { start, end };
+    }
+
+    pub fn start(&self) -> Date {
+        self.start
+    }
+
+    pub fn end(&self) -> Date {
+        self.end
+    }
+
+    pub fn intersection(&self, other: Period) -> Option<Period> {
+        Period::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    // every month that the period touches, in order
+    pub fn months(&self) -> Vec<Month> {
+        let mut months = vec![Month::of(self.start)];
+        let last = Month::of(self.end);
+
+        while months[months.len() - 1] < last {
+            months.push(months[months.len() - 1].next());
+        }
+
+        return months;
+    }
+
+    pub fn contains(&self, date: Date) -> bool {
+        self.start <= date && date <= self.end
+    }
+
+    pub fn days(&self) -> u32 {
+        let days = self.start.days_until(self.end) + 1;
+        assert!(days >= 1);
+
+        return days.try_into().unwrap_or(u32::MAX);
+    }
+
+    // the day of as_of counts as elapsed, so this is 1 on the first day
+    pub fn days_elapsed(&self, as_of: Date) -> u32 {
+        if as_of < self.start {
+            return 0;
+        }
+
+        if as_of > self.end {
+            return self.days();
+        }
+
+        let days = self.start.days_until(as_of) + 1;
+        return days.try_into().unwrap_or(u32::MAX);
+    }
+
+    // the day of as_of counts as remaining too, since money can still be
+    // spent on it
+    pub fn days_remaining(&self, as_of: Date) -> u32 {
+        if as_of < self.start {
+            return self.days();
+        }
+
+        if as_of > self.end {
+            return 0;
+        }
+
+        let days = as_of.days_until(self.end) + 1;
+        return days.try_into().unwrap_or(u32::MAX);
+    }
+}
+
+impl Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} to {}", self.start, self.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_week_of() {
+        let week = Period::week_of(Date::from_str("2025-12-31").unwrap());
+        assert_eq!(week.to_string(), "2025-12-29 to 2026-01-04");
+        assert_eq!(week.days(), 7);
+    }
+
+    fn period(start: &str, end: &str) -> Period {
+        Period::new(Date::from_str(start).unwrap(), Date::from_str(end).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_months() {
+        let months: Vec<String> = period("2025-11-15", "2026-02-01")
+            .months()
+            .iter()
+            .map(Month::to_string)
+            .collect();
+        assert_eq!(months, vec!["2025-11", "2025-12", "2026-01", "2026-02"]);
+    }
+
+    #[test]
+    fn test_intersection() {
+        let quarter = period("2025-07-01", "2025-09-30");
+        let august = Period::month_of(Date::from_str("2025-08-10").unwrap());
+        assert_eq!(quarter.intersection(august), Some(august));
+        assert_eq!(
+            quarter.intersection(period("2025-09-20", "2025-10-05")),
+            Some(period("2025-09-20", "2025-09-30"))
+        );
+        assert_eq!(
+            quarter.intersection(period("2025-10-01", "2025-10-05")),
+            None
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use crate::atomic_file;
+use crate::paths::Paths;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+// the user's named budgets, each with its own directory, and which one is
+// used when no budget is found around the current directory
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct Profiles {
+    pub default: Option<String>,
+    pub profiles: BTreeMap<String, PathBuf>,
+}
+
+impl Profiles {
+    // profiles.json under $XDG_CONFIG_HOME/budget, or ~/.config/budget
+    pub fn config_path() -> Result<PathBuf, Box<dyn Error>> {
+        let config = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home()?.join(".config"),
+        };
+
+        return Ok(config.join("budget").join("profiles.json"));
+    }
+
+    // where a new profile's budget goes when no directory is given:
+    // $XDG_DATA_HOME/budget/<name>, or ~/.local/share/budget/<name>
+    pub fn default_directory(name: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let data = match env::var_os("XDG_DATA_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => home()?.join(".local").join("share"),
+        };
+
+        return Ok(data.join("budget").join(name));
+    }
+
+    // a user who has never created a profile simply has none
+    pub fn load() -> Result<Profiles, Box<dyn Error>> {
+        let path = Self::config_path()?;
+        if !path.exists() {
+            return Ok(Profiles::default());
+        }
+
+        let file = File::open_buffered(&path)
+            .map_err(|e| format!("could not open {}: {}", path.display(), e))?;
+        return Ok(serde_json::from_reader(file)?);
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let path = Self::config_path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        atomic_file::write(path, &serde_json::to_vec_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    pub fn paths(&self, name: &str) -> Result<Paths, Box<dyn Error>> {
+        match self.profiles.get(name) {
+            Some(dir) => Ok(Paths::new(dir)),
+            None => Err(format!(
+                "there is no profile named {}; see budget profile list",
+                name
+            )
+            .into()),
+        }
+    }
+
+    pub fn default_paths(&self) -> Option<Result<Paths, Box<dyn Error>>> {
+        self.default.as_ref().map(|name| self.paths(name))
+    }
+}
+
+pub fn validate_name(name: &str) -> Result<(), Box<dyn Error>> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err("profile names may only contain letters, digits, - and _".into());
+    }
+
+    Ok(())
+}
+
+fn home() -> Result<PathBuf, Box<dyn Error>> {
+    match env::home_dir() {
+        Some(home) => Ok(home),
+        None => Err("could not find the home directory".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("small-business_2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("a/b").is_err());
+        assert!(validate_name("two words").is_err());
+    }
+}
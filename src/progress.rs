@@ -0,0 +1,182 @@
+use crate::colors;
+use crate::date::Date;
+use crate::money::Money;
+use crate::percent::Percent;
+use crate::period::Period;
+use std::fmt::{self, Display};
+
+// how much of a limit has been used, measured against how much of the period
+// has passed
+#[derive(Debug, Clone, Copy)]
+pub struct Progress {
+    limit: Money,
+    spent: Money,
+    days_elapsed: u32,
+    days_remaining: u32,
+    days_total: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Standing {
+    OnTrack,
+    AheadOfPace,
+    OverLimit,
+}
+
+impl Progress {
+    pub fn new(period: Period, as_of: Date, limit: Money, spent: Money) -> Progress {
+        Progress {
+            limit,
+            spent,
+            days_elapsed: period.days_elapsed(as_of),
+            days_remaining: period.days_remaining(as_of),
+            days_total: period.days(),
+        }
+    }
+
+    pub fn limit(&self) -> Money {
+        self.limit
+    }
+
+    pub fn remaining(&self) -> Option<Money> {
+        self.limit.checked_sub(self.spent)
+    }
+
+    pub fn percent_used(&self) -> Option<Percent> {
+        self.spent.percent_of(self.limit)
+    }
+
+    // the share of the limit that has become available so far, assuming it is
+    // spread evenly over the period
+    pub fn expected_to_date(&self) -> Option<Money> {
+        self.limit
+            .checked_scale(self.days_elapsed.into(), self.days_total.into())
+    }
+
+    pub fn left_to_date(&self) -> Option<Money> {
+        self.expected_to_date()?.checked_sub(self.spent)
+    }
+
+    // 100% means spending exactly as fast as the limit accrues
+    pub fn pace(&self) -> Option<Percent> {
+        self.spent.percent_of(self.expected_to_date()?)
+    }
+
+    pub fn daily_allowance(&self) -> Option<Money> {
+        self.remaining()?
+            .checked_scale(1, self.days_remaining.into())
+    }
+
+    // where spending will end up if it continues at the current rate
+    pub fn projected(&self) -> Option<Money> {
+        self.spent
+            .checked_scale(self.days_total.into(), self.days_elapsed.into())
+    }
+
+    pub fn standing(&self) -> Standing {
+        if self.spent > self.limit {
+            return Standing::OverLimit;
+        }
+
+        match self.pace() {
+            Some(pace) if pace > Percent::HUNDRED => Standing::AheadOfPace,
+            _ => Standing::OnTrack,
+        }
+    }
+}
+
+impl Display for Standing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Standing::OnTrack => write!(f, "{}on track{}", colors::green(), colors::reset()),
+            Standing::AheadOfPace => {
+                write!(f, "{}ahead of pace{}", colors::yellow(), colors::reset())
+            }
+            Standing::OverLimit => write!(f, "{}over limit{}", colors::red(), colors::reset()),
+        }
+    }
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.standing())?;
+
+        if let Some(percent) = self.percent_used() {
+            write!(f, ", {} of {} used", percent, self.limit)?;
+        }
+
+        if let Some(left) = self.left_to_date() {
+            write!(f, ", {} left to date", left)?;
+        }
+
+        if let Some(pace) = self.pace() {
+            write!(f, ", {} of pace", pace)?;
+        }
+
+        if let Some(allowance) = self.daily_allowance() {
+            write!(f, ", {}/day for {} days", allowance, self.days_remaining)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn september(day: &str, limit: i64, spent: i64) -> Progress {
+        let as_of = Date::from_str(&format!("2025-09-{}", day)).unwrap();
+        Progress::new(
+            Period::month_of(as_of),
+            as_of,
+            Money::from_cents(limit),
+            Money::from_cents(spent),
+        )
+    }
+
+    #[test]
+    fn test_percent_used_has_decimals() {
+        let progress = september("10", 30_000, 4_512);
+        assert_eq!(
+            progress.percent_used(),
+            Some(Percent::from_basis_points(1_504))
+        );
+    }
+
+    #[test]
+    fn test_pace() {
+        let progress = september("10", 30_000, 15_000);
+        assert_eq!(progress.expected_to_date(), Some(Money::from_cents(10_000)));
+        assert_eq!(progress.left_to_date(), Some(Money::from_cents(-5_000)));
+        assert_eq!(progress.pace(), Some(Percent::from_basis_points(15_000)));
+        assert_eq!(progress.standing(), Standing::AheadOfPace);
+    }
+
+    #[test]
+    fn test_daily_allowance() {
+        let progress = september("21", 30_000, 20_000);
+        assert_eq!(progress.days_remaining, 10);
+        assert_eq!(progress.daily_allowance(), Some(Money::from_cents(1_000)));
+        assert_eq!(
+            september("30", 30_000, 0).daily_allowance(),
+            Some(Money::from_cents(30_000))
+        );
+    }
+
+    #[test]
+    fn test_projected() {
+        let progress = september("15", 30_000, 10_000);
+        assert_eq!(progress.projected(), Some(Money::from_cents(20_000)));
+        assert_eq!(progress.standing(), Standing::OnTrack);
+    }
+
+    #[test]
+    fn test_over_limit() {
+        assert_eq!(
+            september("29", 30_000, 30_001).standing(),
+            Standing::OverLimit
+        );
+    }
+}
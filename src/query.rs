@@ -0,0 +1,621 @@
+use crate::date::{Date, Month};
+use crate::money::Money;
+use crate::tag::{Tag, TagPattern};
+use crate::transaction::Transaction;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use thiserror::Error;
+
+// a parsed query such as
+//   tag:food.** and amount > 20.00 and not comment ~ "uber" group by month sum, count
+// the predicate is optional, so `group by tag sum` summarizes everything
+#[derive(Debug)]
+pub struct Query {
+    pub predicate: Option<Predicate>,
+    pub group_by: Option<GroupBy>,
+    pub aggregates: Vec<Aggregate>,
+}
+
+#[derive(Debug)]
+pub enum Predicate {
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+    Not(Box<Predicate>),
+    Tag(TagPattern),
+    TagEquals(Comparison, Tag),
+    Amount(Comparison, Money),
+    Date(Comparison, Date),
+    Comment(Comparison, String),
+    CommentMatches(Regex),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Tag,
+    Month,
+    Week,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Count,
+    Avg,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum QueryError {
+    #[error("unterminated string in query")]
+    UnterminatedString,
+    #[error("unexpected character '{0}' in query")]
+    UnexpectedCharacter(char),
+    #[error("expected {expected} but found {found}")]
+    Expected { expected: String, found: String },
+    #[error("{0} cannot be compared with '{1}'")]
+    InvalidComparison(String, String),
+    #[error("invalid value '{value}' for {field}: {reason}")]
+    InvalidValue {
+        field: String,
+        value: String,
+        reason: String,
+    },
+}
+
+// one row of a grouped or aggregated result
+#[derive(Debug, PartialEq)]
+pub struct Row {
+    pub group: Option<String>,
+    pub sum: Money,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Symbol(&'static str),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(w) => format!("'{}'", w),
+            Token::Str(s) => format!("\"{}\"", s),
+            Token::Symbol(s) => format!("'{}'", s),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+}
+
+// the longest symbols come first so that <= is not read as < followed by =
+const SYMBOLS: [&str; 10] = ["!=", "<=", ">=", "(", ")", ":", "~", "=", "<", ">"];
+
+fn tokenize(s: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+
+    while let Some(&(i, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == ',' {
+            // commas only separate aggregates, where whitespace works too
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut string = String::new();
+            loop {
+                match chars.next() {
+                    None => return Err(QueryError::UnterminatedString),
+                    Some((_, '"')) => break,
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped)) => string.push(escaped),
+                        None => return Err(QueryError::UnterminatedString),
+                    },
+                    Some((_, c)) => string.push(c),
+                }
+            }
+            tokens.push(Token::Str(string));
+        } else if is_word_char(c) {
+            let mut word = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if !is_word_char(c) {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        } else {
+            let symbol = SYMBOLS
+                .iter()
+                .find(|symbol| s[i..].starts_with(*symbol))
+                .ok_or(QueryError::UnexpectedCharacter(c))?;
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            tokens.push(Token::Symbol(symbol));
+        }
+    }
+
+    return Ok(tokens);
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '.' || c == '-' || c == '*' || c == '_'
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        return token;
+    }
+
+    fn found(&self) -> String {
+        match self.peek() {
+            Some(token) => token.describe(),
+            None => "the end of the query".to_string(),
+        }
+    }
+
+    fn expected<T>(&self, expected: &str) -> Result<T, QueryError> {
+        Err(QueryError::Expected {
+            expected: expected.to_string(),
+            found: self.found(),
+        })
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek().is_some_and(|t| t.is_keyword(keyword)) {
+            self.position += 1;
+            return true;
+        }
+
+        return false;
+    }
+
+    fn eat_symbol(&mut self, symbol: &'static str) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            return true;
+        }
+
+        return false;
+    }
+
+    fn at_clause(&self) -> bool {
+        match self.peek() {
+            None => true,
+            Some(token) => {
+                token.is_keyword("group")
+                    || token.is_keyword("sum")
+                    || token.is_keyword("count")
+                    || token.is_keyword("avg")
+            }
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, QueryError> {
+        let predicate = if self.at_clause() {
+            None
+        } else {
+            Some(self.or()?)
+        };
+
+        let mut group_by = None;
+        if self.eat_keyword("group") {
+            if !self.eat_keyword("by") {
+                return self.expected("'by' after 'group'");
+            }
+
+            group_by = Some(match self.next() {
+                Some(t) if t.is_keyword("tag") => GroupBy::Tag,
+                Some(t) if t.is_keyword("month") => GroupBy::Month,
+                Some(t) if t.is_keyword("week") => GroupBy::Week,
+                _ => {
+                    self.position -= 1;
+                    return self.expected("tag, month or week after 'group by'");
+                }
+            });
+        }
+
+        let mut aggregates = Vec::new();
+        while let Some(token) = self.peek() {
+            let aggregate = if token.is_keyword("sum") {
+                Aggregate::Sum
+            } else if token.is_keyword("count") {
+                Aggregate::Count
+            } else if token.is_keyword("avg") {
+                Aggregate::Avg
+            } else {
+                return self.expected("sum, count, avg or the end of the query");
+            };
+
+            self.position += 1;
+            if !aggregates.contains(&aggregate) {
+                aggregates.push(aggregate);
+            }
+        }
+
+        return Ok(Query {
+            predicate,
+            group_by,
+            aggregates,
+        });
+    }
+
+    fn or(&mut self) -> Result<Predicate, QueryError> {
+        let mut left = self.and()?;
+        while self.eat_keyword("or") {
+            let right = self.and()?;
+            left = Predicate::Or(Box::new(left), Box::new(right));
+        }
+
+        return Ok(left);
+    }
+
+    fn and(&mut self) -> Result<Predicate, QueryError> {
+        let mut left = self.not()?;
+        while self.eat_keyword("and") {
+            let right = self.not()?;
+            left = Predicate::And(Box::new(left), Box::new(right));
+        }
+
+        return Ok(left);
+    }
+
+    fn not(&mut self) -> Result<Predicate, QueryError> {
+        if self.eat_keyword("not") {
+            return Ok(Predicate::Not(Box::new(self.not()?)));
+        }
+
+        return self.primary();
+    }
+
+    fn primary(&mut self) -> Result<Predicate, QueryError> {
+        if self.eat_symbol("(") {
+            let predicate = self.or()?;
+            if !self.eat_symbol(")") {
+                return self.expected("')'");
+            }
+            return Ok(predicate);
+        }
+
+        let field = match self.next() {
+            Some(Token::Word(word)) => word.to_ascii_lowercase(),
+            _ => {
+                self.position -= 1;
+                return self.expected("tag, amount, date, comment or '('");
+            }
+        };
+
+        if field == "tag" && self.eat_symbol(":") {
+            let pattern = self.value("tag")?;
+            let pattern = TagPattern::from_str(&pattern)
+                .map_err(|reason| invalid("tag", &pattern, reason))?;
+            return Ok(Predicate::Tag(pattern));
+        }
+
+        if field == "comment" && self.eat_symbol("~") {
+            let pattern = self.value("comment")?;
+            let regex = Regex::new(&pattern).map_err(|e| invalid("comment", &pattern, e))?;
+            return Ok(Predicate::CommentMatches(regex));
+        }
+
+        let comparison = self.comparison()?;
+        let value = self.value(&field)?;
+
+        match field.as_str() {
+            "tag" | "comment"
+                if !matches!(comparison, Comparison::Equal | Comparison::NotEqual) =>
+            {
+                Err(QueryError::InvalidComparison(
+                    field,
+                    comparison.symbol().to_string(),
+                ))
+            }
+            "tag" => {
+                let tag = Tag::from_str(&value).map_err(|reason| invalid("tag", &value, reason))?;
+                Ok(Predicate::TagEquals(comparison, tag))
+            }
+            "comment" => Ok(Predicate::Comment(comparison, value)),
+            "amount" => {
+                let amount = Money::from_str(&value).map_err(|e| invalid("amount", &value, e))?;
+                Ok(Predicate::Amount(comparison, amount))
+            }
+            "date" => {
+                let date = Date::from_str(&value).map_err(|e| invalid("date", &value, e))?;
+                Ok(Predicate::Date(comparison, date))
+            }
+            _ => Err(QueryError::Expected {
+                expected: "tag, amount, date or comment".to_string(),
+                found: format!("'{}'", field),
+            }),
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Comparison, QueryError> {
+        let comparison = match self.peek() {
+            Some(Token::Symbol("=")) => Comparison::Equal,
+            Some(Token::Symbol("!=")) => Comparison::NotEqual,
+            Some(Token::Symbol("<")) => Comparison::Less,
+            Some(Token::Symbol("<=")) => Comparison::LessOrEqual,
+            Some(Token::Symbol(">")) => Comparison::Greater,
+            Some(Token::Symbol(">=")) => Comparison::GreaterOrEqual,
+            _ => return self.expected("a comparison such as = or >="),
+        };
+
+        self.position += 1;
+        return Ok(comparison);
+    }
+
+    fn value(&mut self, field: &str) -> Result<String, QueryError> {
+        match self.next() {
+            Some(Token::Word(value)) | Some(Token::Str(value)) => Ok(value),
+            _ => {
+                self.position -= 1;
+                self.expected(&format!("a value for {}", field))
+            }
+        }
+    }
+}
+
+fn invalid<E: ToString>(field: &str, value: &str, reason: E) -> QueryError {
+    QueryError::InvalidValue {
+        field: field.to_string(),
+        value: value.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Query, QueryError> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+        };
+
+        return parser.query();
+    }
+}
+
+impl Comparison {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Equal => "=",
+            Comparison::NotEqual => "!=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+        }
+    }
+
+    fn holds<T: Ord>(&self, left: &T, right: &T) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+impl Predicate {
+    pub fn matches(&self, t: &Transaction) -> bool {
+        match self {
+            Predicate::And(left, right) => left.matches(t) && right.matches(t),
+            Predicate::Or(left, right) => left.matches(t) || right.matches(t),
+            Predicate::Not(inner) => !inner.matches(t),
+            Predicate::Tag(pattern) => pattern.matches(&t.tag),
+            Predicate::TagEquals(comparison, tag) => comparison.holds(&t.tag, tag),
+            Predicate::Amount(comparison, amount) => comparison.holds(&t.amount, amount),
+            Predicate::Date(comparison, date) => comparison.holds(&t.date, date),
+            Predicate::Comment(comparison, comment) => comparison.holds(&t.comment, comment),
+            Predicate::CommentMatches(regex) => regex.is_match(&t.comment),
+        }
+    }
+}
+
+impl GroupBy {
+    fn key(&self, t: &Transaction) -> String {
+        match self {
+            GroupBy::Tag => t.tag.to_dotted_string(),
+            GroupBy::Month => Month::of(t.date).to_string(),
+            GroupBy::Week => t.date.iso_week(),
+        }
+    }
+}
+
+impl Row {
+    pub fn avg(&self) -> Option<Money> {
+        self.sum.checked_scale(1, self.count.try_into().ok()?)
+    }
+}
+
+impl Query {
+    pub fn matches(&self, t: &Transaction) -> bool {
+        match &self.predicate {
+            Some(predicate) => predicate.matches(t),
+            None => true,
+        }
+    }
+
+    pub fn is_aggregated(&self) -> bool {
+        self.group_by.is_some() || !self.aggregates.is_empty()
+    }
+
+    // grouped queries get a row per group, in order of the group's name;
+    // otherwise there is a single row for every matching transaction
+    pub fn rows(&self, transactions: &[Transaction]) -> Option<Vec<Row>> {
+        let mut rows: BTreeMap<Option<String>, Row> = BTreeMap::new();
+
+        for t in transactions.iter().filter(|t| self.matches(t)) {
+            let group = self.group_by.map(|g| g.key(t));
+            let row = rows.entry(group.clone()).or_insert(Row {
+                group,
+                sum: Money::from_cents(0),
+                count: 0,
+            });
+
+            row.sum = row.sum.checked_add(t.amount)?;
+            row.count += 1;
+        }
+
+        if rows.is_empty() && self.group_by.is_none() {
+            return Some(vec![Row {
+                group: None,
+                sum: Money::from_cents(0),
+                count: 0,
+            }]);
+        }
+
+        return Some(rows.into_values().collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transactions() -> Vec<Transaction> {
+        vec![
+            Transaction::sample("2025-08-30", 2_500, "food.fast-food", "Uber Eats"),
+            Transaction::sample("2025-09-02", 1_800, "food.fast-food", "uber eats"),
+            Transaction::sample("2025-09-03", 4_000, "food.groceries", "market"),
+            Transaction::sample("2025-09-04", 150_000, "housing.rent", ""),
+        ]
+    }
+
+    fn matching(query: &str) -> Vec<Money> {
+        let query = Query::from_str(query).unwrap();
+        transactions()
+            .into_iter()
+            .filter(|t| query.matches(t))
+            .map(|t| t.amount)
+            .collect()
+    }
+
+    fn cents(amounts: &[i64]) -> Vec<Money> {
+        amounts.iter().copied().map(Money::from_cents).collect()
+    }
+
+    #[test]
+    fn test_example_query() {
+        assert_eq!(
+            matching(
+                r#"tag:food.** and amount > 20.00 and date >= 2025-09-01 and comment ~ "(?i)uber""#
+            ),
+            cents(&[])
+        );
+        assert_eq!(
+            matching(
+                r#"tag:food.** and amount > 15.00 and date >= 2025-09-01 and comment ~ "(?i)uber""#
+            ),
+            cents(&[1_800])
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(
+            matching("tag = housing.rent or tag:food.* and amount >= 40.00"),
+            cents(&[4_000, 150_000])
+        );
+        assert_eq!(
+            matching("(tag = food.fast-food or tag:food.*) and amount < 40.00"),
+            cents(&[2_500, 1_800])
+        );
+        assert_eq!(
+            matching("not tag:food.** and not comment = \"\""),
+            cents(&[])
+        );
+        assert_eq!(matching("NOT tag:food.**"), cents(&[150_000]));
+    }
+
+    #[test]
+    fn test_group_by() {
+        let query = Query::from_str("tag:food.** group by month sum, count avg").unwrap();
+        assert_eq!(
+            query.aggregates,
+            vec![Aggregate::Sum, Aggregate::Count, Aggregate::Avg]
+        );
+
+        let rows = query.rows(&transactions()).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                Row {
+                    group: Some("2025-08".to_string()),
+                    sum: Money::from_cents(2_500),
+                    count: 1
+                },
+                Row {
+                    group: Some("2025-09".to_string()),
+                    sum: Money::from_cents(5_800),
+                    count: 2
+                },
+            ]
+        );
+        assert_eq!(rows[1].avg(), Some(Money::from_cents(2_900)));
+    }
+
+    #[test]
+    fn test_aggregate_without_predicate() {
+        let query = Query::from_str("count").unwrap();
+        let rows = query.rows(&transactions()).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].count, 4);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Query::from_str("amount >").unwrap_err(),
+            QueryError::Expected {
+                expected: "a value for amount".to_string(),
+                found: "the end of the query".to_string()
+            }
+        );
+        assert!(matches!(
+            Query::from_str("amount > twenty"),
+            Err(QueryError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            Query::from_str("comment < \"a\""),
+            Err(QueryError::InvalidComparison(..))
+        ));
+        assert_eq!(
+            Query::from_str("comment ~ \"a").unwrap_err(),
+            QueryError::UnterminatedString
+        );
+        assert!(Query::from_str("group by year").is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use crate::atomic_file;
+use crate::transaction::TransactionId;
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+// one step in a data file's history: what changed on the way to version `to`
+// from the version before it
+pub struct Migration {
+    pub to: u32,
+    pub description: &'static str,
+    apply: fn(&mut Value) -> Result<(), Box<dyn Error>>,
+}
+
+// the versions of one kind of data file. a file without a version field is
+// version 1, which is what every file looked like before versions existed
+pub struct Schema {
+    pub name: &'static str,
+    migrations: &'static [Migration],
+}
+
+pub const TRANSACTIONS: Schema = Schema {
+    name: "transactions",
+    migrations: &[
+        Migration {
+            to: 2,
+            description: "give every transaction an id",
+            apply: assign_ids,
+        },
+        // nothing changes in the file, but versions that would drop the
+        // bank ids when writing it back no longer read it
+        Migration {
+            to: 3,
+            description: "let transactions keep the id their bank gave them",
+            apply: |_| Ok(()),
+        },
+    ],
+};
+
+// transactions.jsonl keeps its version in a record of its own on the first
+// line. it started out with ids, so its history starts later
+pub const TRANSACTIONS_LOG: Schema = Schema {
+    name: "transactions log",
+    migrations: &[
+        // as for transactions.json
+        Migration {
+            to: 2,
+            description: "let transactions keep the id their bank gave them",
+            apply: |_| Ok(()),
+        },
+    ],
+};
+
+pub const LIMITS: Schema = Schema {
+    name: "limits",
+    migrations: &[],
+};
+
+// how data files are written: their version, followed by the data itself
+#[derive(Serialize)]
+pub struct Versioned<'a, T> {
+    pub version: u32,
+    #[serde(flatten)]
+    pub data: &'a T,
+}
+
+impl Schema {
+    pub fn current(&self) -> u32 {
+        self.migrations.len() as u32 + 1
+    }
+
+    pub fn version_of(&self, value: &Value) -> Result<u32, Box<dyn Error>> {
+        let version = match value.get("version") {
+            None => 1,
+            Some(version) => version
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .filter(|v| *v >= 1)
+                .ok_or("the version must be a whole number from 1 up")?,
+        };
+
+        if version > self.current() {
+            return Err(format!(
+                "it is a version {} {} file, but this budget only reads up to version {}; \
+                 a newer budget wrote it",
+                version,
+                self.name,
+                self.current()
+            )
+            .into());
+        }
+
+        return Ok(version);
+    }
+
+    // the steps it takes to bring the file up to date, oldest first
+    pub fn pending(&self, value: &Value) -> Result<&'static [Migration], Box<dyn Error>> {
+        let version = self.version_of(value)?;
+
+        return Ok(&self.migrations[version as usize - 1..]);
+    }
+
+    // upgrades the value to the current version one step at a time
+    pub fn upgrade(&self, value: &mut Value) -> Result<(), Box<dyn Error>> {
+        for migration in self.pending(value)? {
+            (migration.apply)(value).map_err(|e| {
+                format!(
+                    "could not upgrade to version {} ({}): {}",
+                    migration.to, migration.description, e
+                )
+            })?;
+        }
+
+        value
+            .as_object_mut()
+            .ok_or("it is not a JSON object")?
+            .insert("version".to_string(), Value::from(self.current()));
+
+        Ok(())
+    }
+
+    // upgrades the value read from path, first copying the file to
+    // <path>.v<version>.bak, which later writes leave alone. returns whether
+    // there was anything to upgrade; writing the result back is up to the
+    // caller
+    pub fn migrate(&self, path: &Path, value: &mut Value) -> Result<bool, Box<dyn Error>> {
+        let from = self
+            .version_of(value)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        if from == self.current() {
+            return Ok(false);
+        }
+
+        let backup = atomic_file::version_backup_path(path, from);
+        fs::copy(path, &backup)?;
+        self.upgrade(value)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+        eprintln!(
+            "note: upgraded {} from version {} to {}; the old file is kept as {}",
+            path.display(),
+            from,
+            self.current(),
+            backup.display()
+        );
+
+        return Ok(true);
+    }
+
+    // upgrades the value read from path in memory only, for commands that
+    // just read; the file itself is upgraded by the next change to it, under
+    // the data lock, or by budget migrate
+    pub fn upgrade_for_reading(
+        &self,
+        path: &Path,
+        value: &mut Value,
+    ) -> Result<(), Box<dyn Error>> {
+        let from = self
+            .version_of(value)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        if from == self.current() {
+            return Ok(());
+        }
+
+        self.upgrade(value)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        eprintln!(
+            "note: {} is version {}; run budget migrate to upgrade it to version {}",
+            path.display(),
+            from,
+            self.current()
+        );
+
+        Ok(())
+    }
+}
+
+pub fn read(path: &Path) -> Result<Value, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("could not open {}: {}", path.display(), e))?;
+
+    return serde_json::from_str(&contents)
+        .map_err(|e| format!("{} is invalid: {}", path.display(), e).into());
+}
+
+// each id is derived from the transaction and its place in the file, so that
+// reading the same file again gives the same ids. ids that turn out to be
+// taken are replaced when the transactions are loaded
+fn assign_ids(value: &mut Value) -> Result<(), Box<dyn Error>> {
+    let transactions = value
+        .get_mut("transactions")
+        .and_then(Value::as_array_mut)
+        .ok_or("there is no list of transactions")?;
+
+    for (i, t) in transactions.iter_mut().enumerate() {
+        let seed = format!("{} {}", t, i);
+        let t = t
+            .as_object_mut()
+            .ok_or("a transaction is not a JSON object")?;
+        let assigned = t
+            .get("id")
+            .and_then(Value::as_str)
+            .is_some_and(|id| !id.is_empty());
+        if !assigned {
+            t.insert(
+                "id".to_string(),
+                Value::from(TransactionId::derive(&seed).to_string()),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_unversioned_transactions_get_ids() {
+        let mut value = json!({"transactions": [
+            {"date": "2025-09-12", "amount": "4.50", "tag": "food.coffee", "comment": ""},
+            {"date": "2025-09-13", "amount": "3", "tag": "food", "comment": "", "id": "0000000a"},
+        ]});
+        assert_eq!(TRANSACTIONS.version_of(&value).unwrap(), 1);
+        assert_eq!(TRANSACTIONS.pending(&value).unwrap().len(), 2);
+
+        TRANSACTIONS.upgrade(&mut value).unwrap();
+        assert_eq!(value["version"], json!(3));
+        assert_eq!(value["transactions"][0]["id"].as_str().unwrap().len(), 8);
+        assert_eq!(value["transactions"][1]["id"], json!("0000000a"));
+        assert!(TRANSACTIONS.pending(&value).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_newer_and_invalid_versions_are_refused() {
+        assert!(TRANSACTIONS.version_of(&json!({"version": 4})).is_err());
+        assert!(TRANSACTIONS.version_of(&json!({"version": 0})).is_err());
+        assert!(LIMITS.version_of(&json!({"version": "1"})).is_err());
+        assert_eq!(LIMITS.version_of(&json!({"limits": {}})).unwrap(), 1);
+    }
+}
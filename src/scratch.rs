@@ -0,0 +1,38 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+// a directory of a test's own under the system's temporary directory. it is
+// removed when it goes out of scope, so a failing assert does not leave it
+// behind
+pub struct ScratchDir {
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    // the name tells apart the tests of one run, the process id the runs
+    pub fn new(name: &str) -> ScratchDir {
+        let path = std::env::temp_dir().join(format!("budget-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+
+        return ScratchDir { path };
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join<P>(&self, name: P) -> PathBuf
+    where
+        P: AsRef<Path>,
+    {
+        self.path.join(name)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::atomic_file;
+use crate::date::Date;
+use crate::period::Period;
+use crate::tag::Tag;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+// a budget's budget.toml. every setting has a default, so the file only
+// needs the ones that differ, and a budget without the file uses them all
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub display: DisplaySettings,
+    pub budget: BudgetSettings,
+    pub export: ExportSettings,
+    pub import: ImportSettings,
+    pub lock: LockSettings,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DisplaySettings {
+    pub color: bool,
+    pub currency: String,
+    // how many of a leaf's transactions status shows under it
+    pub transactions_per_leaf: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct BudgetSettings {
+    // how long the limits last before they start over
+    pub period: PeriodLength,
+}
+
+// how budget export ledger and beancount name accounts and amounts
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportSettings {
+    // where the money comes from
+    pub funding_account: String,
+    // the account tags are placed under
+    pub expense_account: String,
+    pub commodity: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ImportSettings {
+    // budget import ledger takes the postings to accounts under this one,
+    // and strips it to make their tags
+    pub account_prefix: String,
+    // the tag of bank transactions that no rule matches
+    pub fallback_tag: Tag,
+    // tried in order on a bank transaction's name and memo; the first one
+    // that matches picks its tag
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LockSettings {
+    // how long a command waits for another one to finish before giving up
+    pub timeout_secs: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    // a regular expression, matched regardless of case
+    pub pattern: String,
+    pub tag: Tag,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PeriodLength {
+    #[default]
+    Month,
+    Week,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> DisplaySettings {
+        DisplaySettings {
+            color: true,
+            currency: "$".to_string(),
+            transactions_per_leaf: 3,
+        }
+    }
+}
+
+impl Default for ExportSettings {
+    fn default() -> ExportSettings {
+        ExportSettings {
+            funding_account: "Assets:Checking".to_string(),
+            expense_account: "Expenses".to_string(),
+            commodity: "USD".to_string(),
+        }
+    }
+}
+
+impl Default for ImportSettings {
+    fn default() -> ImportSettings {
+        ImportSettings {
+            account_prefix: "Expenses:".to_string(),
+            fallback_tag: Tag::from_str("uncategorized").unwrap(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl Default for LockSettings {
+    fn default() -> LockSettings {
+        LockSettings { timeout_secs: 10 }
+    }
+}
+
+impl Rule {
+    pub fn regex(&self) -> Result<Regex, regex::Error> {
+        RegexBuilder::new(&self.pattern)
+            .case_insensitive(true)
+            .build()
+    }
+}
+
+impl PeriodLength {
+    pub fn period_of(self, date: Date) -> Period {
+        match self {
+            PeriodLength::Month => Period::month_of(date),
+            PeriodLength::Week => Period::week_of(date),
+        }
+    }
+}
+
+static SETTINGS: RwLock<Option<Arc<Settings>>> = RwLock::new(None);
+
+// the settings of the budget being worked on; the defaults until a budget's
+// settings are installed
+pub fn get() -> Arc<Settings> {
+    let installed = SETTINGS.read().unwrap_or_else(|e| e.into_inner());
+
+    return installed.clone().unwrap_or_default();
+}
+
+// replaces the settings in place, e.g. when status --all-profiles moves on
+// to the next profile's budget
+pub fn install(settings: Settings) {
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(settings));
+}
+
+impl Settings {
+    pub fn from_toml_str(s: &str) -> Result<Settings, Box<dyn Error>> {
+        let settings: Settings = toml::from_str(s)?;
+        settings.validate()?;
+
+        return Ok(settings);
+    }
+
+    pub fn from_toml_file_or_default<P>(path: P) -> Result<Settings, Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Settings::default());
+        }
+
+        let contents = fs::read_to_string(path)?;
+        return Settings::from_toml_str(&contents)
+            .map_err(|e| format!("{} is invalid: {}", path.display(), e).into());
+    }
+
+    pub fn to_toml_file<P>(&self, path: P) -> Result<(), Box<dyn Error>>
+    where
+        P: AsRef<Path>,
+    {
+        self.validate()?;
+        atomic_file::write(path, toml::to_string_pretty(self)?.as_bytes())?;
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let currency = &self.display.currency;
+        if currency.trim().is_empty() || currency.chars().any(|c| c.is_ascii_digit()) {
+            return Err("display.currency must be a symbol without digits, e.g. $".into());
+        }
+
+        if self.display.transactions_per_leaf > 1000 {
+            return Err("display.transactions_per_leaf must be at most 1000".into());
+        }
+
+        if self.lock.timeout_secs > 3600 {
+            return Err("lock.timeout_secs must be at most 3600".into());
+        }
+
+        let export = &self.export;
+        for (key, account) in [
+            ("export.funding_account", &export.funding_account),
+            ("export.expense_account", &export.expense_account),
+        ] {
+            let is_account =
+                account.split(':').all(|c| !c.is_empty()) && !account.contains(char::is_whitespace);
+            if !is_account {
+                return Err(format!("{} must be an account such as Assets:Checking", key).into());
+            }
+        }
+
+        if export.commodity.is_empty() || export.commodity.contains(char::is_whitespace) {
+            return Err("export.commodity must be a commodity such as USD".into());
+        }
+
+        if self.import.account_prefix.trim().is_empty() {
+            return Err("import.account_prefix must be an account such as Expenses:".into());
+        }
+
+        if self.import.fallback_tag.as_slice().depth() == 0 {
+            return Err("import.fallback_tag must not be the root tag".into());
+        }
+
+        for rule in &self.import.rules {
+            rule.regex()
+                .map_err(|e| format!("the import rule for {} is invalid: {}", rule.tag, e))?;
+        }
+
+        Ok(())
+    }
+
+    // the value of a dotted key such as display.currency
+    pub fn get(&self, key: &str) -> Result<toml::Value, Box<dyn Error>> {
+        let mut value = toml::Value::try_from(self)?;
+        for part in key.split('.') {
+            value = value
+                .get(part)
+                .cloned()
+                .ok_or_else(|| format!("there is no setting named {}", key))?;
+        }
+
+        return Ok(value);
+    }
+
+    // sets a dotted key from the text given on the command line, which is
+    // read as the same kind of value the setting already holds
+    pub fn set(&self, key: &str, text: &str) -> Result<Settings, Box<dyn Error>> {
+        let current = self.get(key)?;
+        let value = match current {
+            toml::Value::Boolean(_) => toml::Value::Boolean(
+                text.parse()
+                    .map_err(|_| format!("{} must be true or false", key))?,
+            ),
+            toml::Value::Integer(_) => toml::Value::Integer(
+                text.parse()
+                    .map_err(|_| format!("{} must be a whole number", key))?,
+            ),
+            toml::Value::String(_) => toml::Value::String(text.to_string()),
+            _ => return Err(format!("{} is a group of settings, not a setting", key).into()),
+        };
+
+        let mut table = toml::Value::try_from(self)?;
+        let mut parts: Vec<&str> = key.split('.').collect();
+        let last = parts.pop().ok_or("the setting's name is empty")?;
+        let mut parent = &mut table;
+        for part in parts {
+            parent = parent
+                .get_mut(part)
+                .ok_or_else(|| format!("there is no setting named {}", key))?;
+        }
+        parent
+            .as_table_mut()
+            .ok_or_else(|| format!("there is no setting named {}", key))?
+            .insert(last.to_string(), value);
+
+        let settings: Settings = table
+            .try_into()
+            .map_err(|e| format!("invalid value for {}: {}", key, e))?;
+        settings.validate()?;
+
+        return Ok(settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_settings_are_defaults() {
+        let settings = Settings::from_toml_str("[display]\ncurrency = \"€\"\n").unwrap();
+        assert_eq!(settings.display.currency, "€");
+        assert_eq!(settings.display.transactions_per_leaf, 3);
+        assert_eq!(settings.budget.period, PeriodLength::Month);
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        assert!(Settings::from_toml_str("[display]\ncolour = false\n").is_err());
+        assert!(Settings::from_toml_str("[display]\ncurrency = \"\"\n").is_err());
+        assert!(Settings::from_toml_str("[budget]\nperiod = \"fortnight\"\n").is_err());
+        assert!(Settings::from_toml_str("[export]\nfunding_account = \"My Bank\"\n").is_err());
+    }
+
+    #[test]
+    fn test_import_rules() {
+        let settings = Settings::from_toml_str(
+            "[import]\nfallback_tag = \"misc\"\n\n\
+             [[import.rules]]\npattern = \"starbucks|peet's\"\ntag = \"food.coffee\"\n",
+        )
+        .unwrap();
+        assert_eq!(settings.import.rules.len(), 1);
+        assert!(
+            settings.import.rules[0]
+                .regex()
+                .unwrap()
+                .is_match("STARBUCKS #123")
+        );
+
+        let invalid = "[[import.rules]]\npattern = \"(\"\ntag = \"food\"\n";
+        assert!(Settings::from_toml_str(invalid).is_err());
+        assert!(Settings::from_toml_str("[import]\nfallback_tag = \".\"\n").is_err());
+
+        // settings written back out read the same
+        let toml = toml::to_string_pretty(&settings).unwrap();
+        assert_eq!(Settings::from_toml_str(&toml).unwrap(), settings);
+    }
+
+    #[test]
+    fn test_get_and_set() {
+        let settings = Settings::default();
+        assert_eq!(
+            settings.get("display.color").unwrap(),
+            toml::Value::Boolean(true)
+        );
+        assert!(settings.get("display.colour").is_err());
+
+        let settings = settings.set("budget.period", "week").unwrap();
+        assert_eq!(settings.budget.period, PeriodLength::Week);
+        let settings = settings.set("display.transactions_per_leaf", "5").unwrap();
+        assert_eq!(settings.display.transactions_per_leaf, 5);
+        let settings = settings.set("lock.timeout_secs", "30").unwrap();
+        assert_eq!(settings.lock.timeout_secs, 30);
+
+        assert!(settings.set("display.color", "yes").is_err());
+        assert!(settings.set("budget.period", "fortnight").is_err());
+        assert!(settings.set("display", "x").is_err());
+    }
+}
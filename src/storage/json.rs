@@ -0,0 +1,48 @@
+use super::{Storage, apply};
+use crate::limits::Limits;
+use crate::paths::Paths;
+use crate::transaction::{Transaction, TransactionId, Transactions};
+use std::error::Error;
+use std::path::PathBuf;
+
+// the whole of transactions.json is read for every change and written back,
+// sorted and pretty-printed
+pub struct JsonStorage {
+    transactions: PathBuf,
+    limits: PathBuf,
+}
+
+impl JsonStorage {
+    pub fn new(paths: &Paths) -> JsonStorage {
+        JsonStorage {
+            transactions: paths.transactions(),
+            limits: paths.limits(),
+        }
+    }
+}
+
+impl Storage for JsonStorage {
+    fn load(&self) -> Result<Transactions, Box<dyn Error>> {
+        Transactions::from_json_file(&self.transactions)
+    }
+
+    fn replace(
+        &mut self,
+        take_out: &[TransactionId],
+        put_in: Vec<Transaction>,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>> {
+        let mut transactions = self.load()?;
+        let taken = apply(&mut transactions, take_out, put_in)?;
+        transactions.to_json_file(&self.transactions)?;
+
+        return Ok(taken);
+    }
+
+    fn limits(&self) -> Result<Limits, Box<dyn Error>> {
+        Limits::from_json_file_or_empty(&self.limits)
+    }
+
+    fn save_limits(&mut self, limits: &Limits) -> Result<(), Box<dyn Error>> {
+        limits.to_json_file(&self.limits)
+    }
+}
@@ -0,0 +1,453 @@
+use super::{Storage, apply};
+use crate::atomic_file;
+use crate::limits::Limits;
+use crate::paths::Paths;
+use crate::schema;
+use crate::transaction::{Transaction, TransactionId, Transactions};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+// once the log has this many records more than twice what it takes to hold
+// the live transactions, a change compacts it
+const COMPACTION_SLACK: usize = 100;
+
+// transactions.jsonl is only ever appended to, one record per line. a
+// transaction's latest record wins, so an update is the new version of the
+// transaction and a delete is a tombstone with its id. compaction rewrites
+// the log with just the live transactions
+pub struct JsonlStorage {
+    log: PathBuf,
+    limits: PathBuf,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Record {
+    Transaction(Transaction),
+    Deleted(TransactionId),
+    // only ever the first record; a log without it is version 1
+    Version(u32),
+}
+
+impl JsonlStorage {
+    pub fn new(paths: &Paths) -> JsonlStorage {
+        JsonlStorage {
+            log: paths.transactions_log(),
+            limits: paths.limits(),
+        }
+    }
+
+    // one write and one sync, however many records there are. an
+    // interrupted append may have left an unterminated line at the end,
+    // which is cut off first so that the records start on a line of their own
+    fn write_records(&self, records: &[Record]) -> Result<(), Box<dyn Error>> {
+        let mut lines = Vec::new();
+        for record in records {
+            serde_json::to_writer(&mut lines, record)?;
+            lines.push(b'\n');
+        }
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.log)?;
+        let end = complete_length(&mut file)?;
+        file.set_len(end)?;
+        file.seek(SeekFrom::Start(end))?;
+        file.write_all(&lines)?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+
+    fn replay(&self) -> Result<(Transactions, usize), Box<dyn Error>> {
+        let contents = read(&self.log)?;
+        let from = version(&contents).map_err(|e| format!("{}: {}", self.log.display(), e))?;
+        if from < schema::TRANSACTIONS_LOG.current() {
+            eprintln!(
+                "note: {} is version {}; run budget migrate to upgrade it to version {}",
+                self.log.display(),
+                from,
+                schema::TRANSACTIONS_LOG.current()
+            );
+        }
+
+        return replay(&contents);
+    }
+
+    // returns how many records were dropped
+    pub fn compact(&self) -> Result<usize, Box<dyn Error>> {
+        let (transactions, records) = self.replay()?;
+        write_log(&self.log, &transactions)?;
+
+        return Ok(records - transactions.transactions.len());
+    }
+}
+
+fn needs_compacting(records: usize, live: usize) -> bool {
+    records > live * 2 + COMPACTION_SLACK
+}
+
+fn read(path: &Path) -> Result<String, Box<dyn Error>> {
+    return fs::read_to_string(path)
+        .map_err(|e| format!("could not open {}: {}", path.display(), e).into());
+}
+
+// the version record a log starts with
+pub fn header() -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut header = serde_json::to_vec(&Record::Version(schema::TRANSACTIONS_LOG.current()))?;
+    header.push(b'\n');
+
+    return Ok(header);
+}
+
+// the version of the log, from its first record
+pub fn version(contents: &str) -> Result<u32, Box<dyn Error>> {
+    let first = contents.lines().find(|line| !line.trim().is_empty());
+    let value = match first.map(serde_json::from_str::<Record>) {
+        Some(Ok(Record::Version(version))) => json!({ "version": version }),
+        _ => json!({}),
+    };
+
+    return schema::TRANSACTIONS_LOG.version_of(&value);
+}
+
+// for changing the log, under the data lock: a log written by an older
+// version is copied to <path>.v<version>.bak and written again with the
+// current version's record. nothing in the records themselves changes yet
+pub fn upgrade_log(path: &Path) -> Result<(), Box<dyn Error>> {
+    let contents = read(path)?;
+    let from = version(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if from == schema::TRANSACTIONS_LOG.current() {
+        return Ok(());
+    }
+
+    let backup = atomic_file::version_backup_path(path, from);
+    fs::copy(path, &backup)?;
+    let (transactions, _) = replay(&contents)?;
+    write_log(path, &transactions)?;
+
+    eprintln!(
+        "note: upgraded {} from version {} to {}; the old file is kept as {}",
+        path.display(),
+        from,
+        schema::TRANSACTIONS_LOG.current(),
+        backup.display()
+    );
+
+    Ok(())
+}
+
+// replaces the log with its version and one record per transaction, in order
+pub fn write_log<P>(path: P, transactions: &Transactions) -> Result<(), Box<dyn Error>>
+where
+    P: AsRef<Path>,
+{
+    let mut contents = header()?;
+    for t in &transactions.transactions {
+        serde_json::to_writer(&mut contents, &Record::Transaction(t.clone()))?;
+        contents.push(b'\n');
+    }
+    atomic_file::write(path, &contents)?;
+
+    Ok(())
+}
+
+// replays the records in order, returning the transactions and how many
+// records of them there were. a last line without a newline is what an
+// interrupted append leaves behind, so it is skipped rather than refused
+pub fn replay(contents: &str) -> Result<(Transactions, usize), Box<dyn Error>> {
+    let mut transactions = Transactions::empty();
+    let mut records = 0;
+    let complete = contents.ends_with('\n');
+    let lines: Vec<&str> = contents.lines().collect();
+    let first = lines.iter().position(|line| !line.trim().is_empty());
+
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record: Record = match serde_json::from_str(line) {
+            Ok(record) => record,
+            Err(_) if i + 1 == lines.len() && !complete => {
+                eprintln!("warning: skipped an incomplete last line in transactions.jsonl");
+                break;
+            }
+            Err(e) => return Err(format!("line {} of transactions.jsonl: {}", i + 1, e).into()),
+        };
+
+        match record {
+            Record::Version(version) if Some(i) == first => {
+                schema::TRANSACTIONS_LOG
+                    .version_of(&json!({ "version": version }))
+                    .map_err(|e| format!("transactions.jsonl: {}", e))?;
+            }
+            Record::Version(_) => {
+                return Err(format!(
+                    "line {} of transactions.jsonl: only the first line may hold the version",
+                    i + 1
+                )
+                .into());
+            }
+            Record::Transaction(t) => {
+                records += 1;
+                if let Some(index) = transactions.position(&t.id) {
+                    transactions.transactions[index] = t;
+                } else {
+                    transactions.transactions.push(t);
+                }
+            }
+            Record::Deleted(id) => {
+                records += 1;
+                if let Some(index) = transactions.position(&id) {
+                    transactions.transactions.remove(index);
+                }
+            }
+        }
+    }
+
+    transactions.transactions.sort();
+    return Ok((transactions, records));
+}
+
+// the length of the file up to and including its last newline. only the last
+// byte is read unless the file ends in the middle of a line
+fn complete_length(file: &mut File) -> Result<u64, Box<dyn Error>> {
+    let length = file.metadata()?.len();
+    if length == 0 {
+        return Ok(0);
+    }
+
+    let mut last = [0];
+    file.seek(SeekFrom::Start(length - 1))?;
+    file.read_exact(&mut last)?;
+    if last[0] == b'\n' {
+        return Ok(length);
+    }
+
+    let mut contents = Vec::new();
+    file.rewind()?;
+    file.read_to_end(&mut contents)?;
+    let end = contents
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map_or(0, |i| i + 1);
+
+    return Ok(end as u64);
+}
+
+impl Storage for JsonlStorage {
+    fn load(&self) -> Result<Transactions, Box<dyn Error>> {
+        let (transactions, _) = self.replay()?;
+
+        return Ok(transactions);
+    }
+
+    // a transaction that is taken out and put back in only needs its new
+    // record, which supersedes the old one. the log is read once, for the
+    // check and for counting its records, and compacted from what was read
+    fn replace(
+        &mut self,
+        take_out: &[TransactionId],
+        put_in: Vec<Transaction>,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>> {
+        let (mut transactions, records) = self.replay()?;
+        let taken = apply(&mut transactions, take_out, put_in.clone())?;
+
+        let mut new: Vec<Record> = take_out
+            .iter()
+            .filter(|id| put_in.iter().all(|t| t.id != **id))
+            .map(|id| Record::Deleted(id.clone()))
+            .collect();
+        new.extend(put_in.into_iter().map(Record::Transaction));
+
+        if needs_compacting(records + new.len(), transactions.transactions.len()) {
+            write_log(&self.log, &transactions)?;
+        } else {
+            self.write_records(&new)?;
+        }
+
+        return Ok(taken);
+    }
+
+    fn limits(&self) -> Result<Limits, Box<dyn Error>> {
+        Limits::from_json_file_or_empty(&self.limits)
+    }
+
+    fn save_limits(&mut self, limits: &Limits) -> Result<(), Box<dyn Error>> {
+        limits.to_json_file(&self.limits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scratch::ScratchDir;
+    use std::str::FromStr;
+
+    fn line(record: &Record) -> String {
+        serde_json::to_string(record).unwrap() + "\n"
+    }
+
+    // the directory is removed when the test is done with the storage
+    fn storage(name: &str) -> (JsonlStorage, ScratchDir) {
+        let directory = ScratchDir::new(&format!("jsonl-{}", name));
+        fs::write(directory.join("transactions.jsonl"), header().unwrap()).unwrap();
+
+        return (JsonlStorage::new(&Paths::new(directory.path())), directory);
+    }
+
+    fn coffee(id: &str, cents: i64) -> Transaction {
+        Transaction::sample("2025-09-12", cents, "food.coffee", "").with_id(id)
+    }
+
+    #[test]
+    fn test_latest_record_wins() {
+        let log = [
+            line(&Record::Transaction(coffee("0000000a", 450))),
+            line(&Record::Transaction(coffee("0000000b", 300))),
+            line(&Record::Transaction(coffee("0000000a", 500))),
+            line(&Record::Deleted(
+                TransactionId::from_str("0000000b").unwrap(),
+            )),
+        ]
+        .concat();
+
+        let (transactions, records) = replay(&log).unwrap();
+        assert_eq!(transactions.transactions, vec![coffee("0000000a", 500)]);
+        assert_eq!(records, 4);
+    }
+
+    #[test]
+    fn test_incomplete_last_line_is_skipped() {
+        let log = line(&Record::Transaction(coffee("0000000a", 450))) + "{\"transaction\": {\"da";
+        assert_eq!(replay(&log).unwrap().0.transactions.len(), 1);
+
+        let log = "{\"transaction\": {\"da\n".to_string()
+            + &line(&Record::Transaction(coffee("0000000a", 450)));
+        assert!(replay(&log).is_err());
+    }
+
+    #[test]
+    fn test_append_after_incomplete_last_line() {
+        let (mut storage, _directory) = storage("torn");
+        storage.append(coffee("0000000a", 450)).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&storage.log).unwrap();
+        file.write_all(b"{\"transaction\": {\"da").unwrap();
+
+        storage.append(coffee("0000000b", 300)).unwrap();
+        storage.append(coffee("0000000c", 200)).unwrap();
+        assert_eq!(storage.load().unwrap().transactions.len(), 3);
+    }
+
+    #[test]
+    fn test_replace_is_all_or_nothing() {
+        let (mut storage, _directory) = storage("replace");
+        let a = TransactionId::from_str("0000000a").unwrap();
+        let b = TransactionId::from_str("0000000b").unwrap();
+        storage
+            .append_all(vec![coffee("0000000a", 450), coffee("0000000b", 300)])
+            .unwrap();
+
+        assert!(
+            storage
+                .replace(&[a.clone(), b.clone(), b.clone()], Vec::new())
+                .is_err()
+        );
+        assert!(
+            storage
+                .replace(std::slice::from_ref(&a), vec![coffee("0000000b", 100)])
+                .is_err()
+        );
+        assert_eq!(storage.load().unwrap().transactions.len(), 2);
+
+        let taken = storage
+            .replace(&[a, b], vec![coffee("0000000a", 500)])
+            .unwrap();
+        assert_eq!(taken.len(), 2);
+        assert_eq!(
+            storage.load().unwrap().transactions,
+            vec![coffee("0000000a", 500)]
+        );
+        assert_eq!(fs::read_to_string(&storage.log).unwrap().lines().count(), 5);
+    }
+
+    #[test]
+    fn test_compact() {
+        let (mut storage, _directory) = storage("compact");
+        storage
+            .append_all(vec![coffee("0000000a", 450), coffee("0000000b", 300)])
+            .unwrap();
+        storage.update(coffee("0000000a", 500)).unwrap();
+        storage
+            .delete(&[TransactionId::from_str("0000000b").unwrap()])
+            .unwrap();
+
+        assert_eq!(storage.compact().unwrap(), 3);
+        assert_eq!(
+            fs::read_to_string(&storage.log).unwrap(),
+            line(&Record::Version(schema::TRANSACTIONS_LOG.current()))
+                + &line(&Record::Transaction(coffee("0000000a", 500)))
+        );
+        assert_eq!(storage.compact().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_changes_compact_past_the_threshold() {
+        let (mut storage, _directory) = storage("threshold");
+        storage.append(coffee("0000000a", 1)).unwrap();
+        for cents in 2..=COMPACTION_SLACK as i64 + 10 {
+            storage.update(coffee("0000000a", cents)).unwrap();
+            let records = fs::read_to_string(&storage.log).unwrap().lines().count();
+            assert!(records <= 3 + COMPACTION_SLACK);
+        }
+
+        let records = fs::read_to_string(&storage.log).unwrap().lines().count();
+        assert!(records < 20);
+        assert_eq!(
+            storage.load().unwrap().transactions,
+            vec![coffee("0000000a", COMPACTION_SLACK as i64 + 10)]
+        );
+    }
+
+    #[test]
+    fn test_version_record() {
+        let transaction = line(&Record::Transaction(coffee("0000000a", 450)));
+        assert_eq!(version(&transaction).unwrap(), 1);
+        assert_eq!(replay(&transaction).unwrap().1, 1);
+
+        let log = line(&Record::Version(2)) + &transaction;
+        assert_eq!(version(&log).unwrap(), 2);
+        assert_eq!(replay(&log).unwrap().1, 1);
+
+        assert!(replay(&(transaction.clone() + &line(&Record::Version(2)))).is_err());
+        assert!(version(&(line(&Record::Version(3)) + &transaction)).is_err());
+        assert!(replay(&(line(&Record::Version(3)) + &transaction)).is_err());
+    }
+
+    #[test]
+    fn test_upgrade_log() {
+        let (storage, _directory) = storage("upgrade");
+        let old = line(&Record::Transaction(coffee("0000000a", 450)));
+        fs::write(&storage.log, &old).unwrap();
+
+        upgrade_log(&storage.log).unwrap();
+        let contents = fs::read_to_string(&storage.log).unwrap();
+        assert_eq!(
+            version(&contents).unwrap(),
+            schema::TRANSACTIONS_LOG.current()
+        );
+        assert_eq!(replay(&contents).unwrap().0.transactions.len(), 1);
+        assert_eq!(
+            fs::read_to_string(atomic_file::version_backup_path(&storage.log, 1)).unwrap(),
+            old
+        );
+    }
+}
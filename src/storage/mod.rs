@@ -0,0 +1,156 @@
+use crate::date::Date;
+use crate::limits::Limits;
+use crate::lock::DataLock;
+use crate::paths::Paths;
+use crate::transaction::{Transaction, TransactionId, Transactions};
+use clap::ValueEnum;
+use std::error::Error;
+use std::path::PathBuf;
+
+pub mod json;
+pub mod jsonl;
+
+pub use json::JsonStorage;
+pub use jsonl::JsonlStorage;
+
+// where a budget's current transactions and limits are kept. archived months
+// are not part of it; they are read through Archive
+pub trait Storage {
+    // every current transaction, sorted
+    fn load(&self) -> Result<Transactions, Box<dyn Error>>;
+
+    // takes out the transactions with the given ids and puts in the new ones
+    // with a single write, so that a change of several transactions is
+    // either all made or not at all. returns what was taken out
+    fn replace(
+        &mut self,
+        take_out: &[TransactionId],
+        put_in: Vec<Transaction>,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>>;
+
+    fn append(&mut self, t: Transaction) -> Result<(), Box<dyn Error>> {
+        self.replace(&[], vec![t])?;
+
+        Ok(())
+    }
+
+    // for adding many at once, e.g. when importing
+    fn append_all(&mut self, transactions: Vec<Transaction>) -> Result<(), Box<dyn Error>> {
+        self.replace(&[], transactions)?;
+
+        Ok(())
+    }
+
+    // replaces the transaction that has the same id
+    fn update(&mut self, t: Transaction) -> Result<(), Box<dyn Error>> {
+        let id = t.id.clone();
+        self.replace(&[id], vec![t])?;
+
+        Ok(())
+    }
+
+    // returns the deleted transactions
+    fn delete(&mut self, ids: &[TransactionId]) -> Result<Vec<Transaction>, Box<dyn Error>> {
+        self.replace(ids, Vec::new())
+    }
+
+    // the current transactions between the bounds, inclusive
+    fn range(
+        &self,
+        from: Option<Date>,
+        to: Option<Date>,
+    ) -> Result<Vec<Transaction>, Box<dyn Error>> {
+        let mut transactions = self.load()?.transactions;
+        transactions
+            .retain(|t| from.is_none_or(|from| t.date >= from) && to.is_none_or(|to| t.date <= to));
+
+        return Ok(transactions);
+    }
+
+    fn limits(&self) -> Result<Limits, Box<dyn Error>>;
+
+    fn save_limits(&mut self, limits: &Limits) -> Result<(), Box<dyn Error>>;
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    // transactions.json, rewritten whole on every change
+    Json,
+    // transactions.jsonl, appended to on every change
+    Jsonl,
+}
+
+impl Format {
+    // a budget keeps its transactions in transactions.jsonl when that file
+    // exists, and in transactions.json otherwise
+    pub fn of(paths: &Paths) -> Format {
+        if paths.transactions_log().exists() {
+            return Format::Jsonl;
+        }
+
+        return Format::Json;
+    }
+
+    pub fn path(self, paths: &Paths) -> PathBuf {
+        match self {
+            Format::Json => paths.transactions(),
+            Format::Jsonl => paths.transactions_log(),
+        }
+    }
+
+    // writes a complete file in this format
+    pub fn write(self, paths: &Paths, transactions: &Transactions) -> Result<(), Box<dyn Error>> {
+        match self {
+            Format::Json => transactions.to_json_file(paths.transactions()),
+            Format::Jsonl => jsonl::write_log(paths.transactions_log(), transactions),
+        }
+    }
+}
+
+// for commands that change the budget: data files written by an older
+// version are upgraded first, which only happens under the data lock
+pub fn open_for_change(
+    paths: &Paths,
+    _lock: &DataLock,
+) -> Result<Box<dyn Storage>, Box<dyn Error>> {
+    match Format::of(paths) {
+        Format::Json => Transactions::upgrade_json_file(paths.transactions())?,
+        Format::Jsonl => jsonl::upgrade_log(&paths.transactions_log())?,
+    }
+    Limits::upgrade_json_file(paths.limits())?;
+
+    return Ok(open(paths));
+}
+
+pub fn open(paths: &Paths) -> Box<dyn Storage> {
+    match Format::of(paths) {
+        Format::Json => Box::new(JsonStorage::new(paths)),
+        Format::Jsonl => Box::new(JsonlStorage::new(paths)),
+    }
+}
+
+// makes the change of Storage::replace to transactions in memory, refusing it
+// when a transaction to take out is missing or one to put in already exists
+fn apply(
+    transactions: &mut Transactions,
+    take_out: &[TransactionId],
+    put_in: Vec<Transaction>,
+) -> Result<Vec<Transaction>, Box<dyn Error>> {
+    let mut taken = Vec::new();
+    for id in take_out {
+        let index = transactions
+            .position(id)
+            .ok_or_else(|| format!("no transaction with id {} found", id))?;
+        taken.push(transactions.transactions.remove(index));
+    }
+
+    for t in put_in {
+        if transactions.position(&t.id).is_some() {
+            return Err(format!("transaction {} already exists", t.id).into());
+        }
+        transactions.transactions.push(t);
+    }
+    transactions.transactions.sort();
+
+    return Ok(taken);
+}
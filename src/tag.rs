@@ -50,6 +50,11 @@ impl FromStr for Tag {
 
 // TODO: implement Deref for the TagSlice and merge the methods
 impl<'a> Tag {
+    // the tag as written in files, without any colors
+    pub fn to_dotted_string(&self) -> String {
+        self.categories.join(".")
+    }
+
     pub fn as_slice(&'a self) -> TagSlice<'a> {
         TagSlice {
             slice: &self.categories[..],
@@ -57,6 +62,12 @@ impl<'a> Tag {
     }
 }
 
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_slice())
+    }
+}
+
 impl fmt::Display for TagError {
     fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
         todo!("Trying to serialize a TagError; it needs to look nice!");
@@ -66,15 +77,15 @@ impl fmt::Display for TagError {
 impl<'a> fmt::Display for TagSlice<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.slice.is_empty() {
-            return write!(f, "{}∀{}", colors::YELLOW, colors::RESET);
+            return write!(f, "{}∀{}", colors::yellow(), colors::reset());
         }
 
         write!(
             f,
             "{}{}{}",
-            colors::YELLOW,
+            colors::yellow(),
             self.slice.join("."),
-            colors::RESET
+            colors::reset()
         )
     }
 }
@@ -94,7 +105,74 @@ impl Serialize for Tag {
     where
         S: Serializer,
     {
-        serializer.serialize_str(self.categories.join(".").as_str())
+        serializer.serialize_str(self.to_dotted_string().as_str())
+    }
+}
+
+// a tag where a segment can be * to match any one segment, or ** to match any
+// number of segments, including none
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct TagPattern {
+    segments: Vec<PatternSegment>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+enum PatternSegment {
+    Literal(String),
+    AnySegment,
+    AnySegments,
+}
+
+impl FromStr for TagPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TagPattern, Self::Err> {
+        if s == "." {
+            return Ok(TagPattern {
+                segments: Vec::new(),
+            });
+        }
+
+        let mut segments = Vec::new();
+        for segment in s.split('.') {
+            let segment = match segment {
+                "*" => PatternSegment::AnySegment,
+                "**" => PatternSegment::AnySegments,
+                literal => {
+                    let tag = Tag::from_str(literal)?;
+                    PatternSegment::Literal(tag.categories.join("."))
+                }
+            };
+            segments.push(segment);
+        }
+
+        return Ok(TagPattern { segments });
+    }
+}
+
+impl TagPattern {
+    pub fn matches(&self, tag: &Tag) -> bool {
+        matches_segments(&self.segments, &tag.categories)
+    }
+}
+
+fn matches_segments(pattern: &[PatternSegment], categories: &[String]) -> bool {
+    match pattern.split_first() {
+        None => categories.is_empty(),
+        Some((PatternSegment::AnySegments, rest)) => {
+            (0..=categories.len()).any(|skip| matches_segments(rest, &categories[skip..]))
+        }
+        Some((segment, rest)) => match categories.split_first() {
+            None => false,
+            Some((category, categories)) => {
+                let segment_matches = match segment {
+                    PatternSegment::Literal(literal) => literal == category,
+                    PatternSegment::AnySegment | PatternSegment::AnySegments => true,
+                };
+
+                segment_matches && matches_segments(rest, categories)
+            }
+        },
     }
 }
 
@@ -121,6 +199,17 @@ impl<'a> TagSlice<'a> {
     pub fn depth(&self) -> usize {
         self.slice.len()
     }
+
+    // true for strict ancestors only, so a tag is not its own ancestor
+    pub fn is_ancestor_of(&self, other: TagSlice) -> bool {
+        other.slice.len() > self.slice.len() && other.slice.starts_with(self.slice)
+    }
+
+    pub fn to_tag(self) -> Tag {
+        Tag {
+            categories: self.slice.to_vec(),
+        }
+    }
 }
 
 pub struct TagParents<'a> {
@@ -156,4 +245,31 @@ mod tests {
         assert_eq!(parents.next().unwrap().depth(), 0);
         assert_eq!(parents.next(), None);
     }
+
+    #[test]
+    fn test_pattern() {
+        let tag = Tag::from_str("food.fast-food.burgers").unwrap();
+        let matches = |pattern| TagPattern::from_str(pattern).unwrap().matches(&tag);
+
+        assert!(matches("food.**"));
+        assert!(matches("**"));
+        assert!(matches("food.*.burgers"));
+        assert!(matches("**.burgers"));
+        assert!(matches("food.fast-food.burgers.**"));
+        assert!(!matches("food.*"));
+        assert!(!matches("food"));
+        assert!(!matches("housing.**"));
+        assert!(TagPattern::from_str("food..*").is_err());
+    }
+
+    #[test]
+    fn test_is_ancestor_of() {
+        let food = Tag::from_str("food").unwrap();
+        let fast_food = Tag::from_str("food.fast-food").unwrap();
+        let root = Tag::from_str(".").unwrap();
+        assert!(food.as_slice().is_ancestor_of(fast_food.as_slice()));
+        assert!(root.as_slice().is_ancestor_of(food.as_slice()));
+        assert!(!fast_food.as_slice().is_ancestor_of(food.as_slice()));
+        assert!(!food.as_slice().is_ancestor_of(food.as_slice()));
+    }
 }
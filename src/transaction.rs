@@ -1,9 +1,17 @@
+use crate::atomic_file;
 use crate::date::Date;
+use crate::schema::{self, Versioned};
 use crate::{Money, Tag, tag::TagSlice};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashSet,
+    error::Error,
     fmt::{self, Display},
-    io::{Read, Write},
+    hash::{BuildHasher, Hasher, RandomState},
+    io::Write,
+    path::Path,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -12,6 +20,98 @@ pub struct Transaction {
     pub amount: Money,
     pub tag: Tag,
     pub comment: String,
+    // files written before transactions had ids leave this unassigned until
+    // they are upgraded with Transactions::upgrade_json_file
+    #[serde(default)]
+    pub id: TransactionId,
+    // the id the bank gave it, for transactions imported from bank files,
+    // so that importing the same file again adds nothing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bank_id: Option<String>,
+}
+
+// eight hex digits, unique within a transactions file
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct TransactionId(String);
+
+#[derive(thiserror::Error, Debug)]
+#[error("transaction ids are eight hex digits, e.g. 3f9a0c12")]
+pub struct TransactionIdError;
+
+impl TransactionId {
+    // RandomState is seeded randomly per process and the seed changes with
+    // every new state, so ids made in quick succession still differ
+    pub fn generate() -> TransactionId {
+        let mut hasher = RandomState::new().build_hasher();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        hasher.write_u128(nanos);
+
+        return TransactionId(format!("{:08x}", hasher.finish() as u32));
+    }
+
+    // the same seed always gives the same id, so that transactions of a file
+    // written before ids existed have the same ids every time it is read,
+    // even before the file is upgraded. FNV-1a, which unlike the hashers of
+    // std is sure to stay the same from one build to the next
+    pub fn derive(seed: &str) -> TransactionId {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in seed.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+
+        return TransactionId(format!("{:08x}", (hash ^ (hash >> 32)) as u32));
+    }
+
+    pub fn is_assigned(&self) -> bool {
+        !self.0.is_empty()
+    }
+}
+
+impl FromStr for TransactionId {
+    type Err = TransactionIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 8 || !s.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(TransactionIdError);
+        }
+
+        return Ok(TransactionId(s.to_ascii_lowercase()));
+    }
+}
+
+impl Display for TransactionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// picks out transactions the way remove and edit do: either the id or the tag
+// and amount must be given, and everything that is given must match
+#[derive(Debug)]
+pub struct Selector {
+    pub id: Option<TransactionId>,
+    pub tag: Option<Tag>,
+    pub amount: Option<Money>,
+    pub date: Option<Date>,
+    pub comment: Option<String>,
+}
+
+impl Selector {
+    pub fn matches(&self, t: &Transaction) -> bool {
+        self.id.as_ref().is_none_or(|id| *id == t.id)
+            && self.tag.as_ref().is_none_or(|tag| *tag == t.tag)
+            && self.amount.is_none_or(|amount| amount == t.amount)
+            && self.date.is_none_or(|date| date == t.date)
+            && self
+                .comment
+                .as_ref()
+                .is_none_or(|comment| *comment == t.comment)
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -20,21 +120,109 @@ pub struct Transactions {
 }
 
 impl Transactions {
-    pub fn from_json_reader<R>(r: R) -> Result<Self, serde_json::Error>
-    where
-        R: Read,
-    {
-        let mut transactions: Self = serde_json::from_reader(r)?;
+    // for reading only: a file written by an older version is upgraded in
+    // memory, and the file is left as it is. the ids it is given are the ones
+    // upgrade_json_file will write, so they can be passed to --id before then
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        let mut value = schema::read(path)?;
+        schema::TRANSACTIONS.upgrade_for_reading(path, &mut value)?;
+        let mut transactions = Self::from_value(path, value)?;
+        transactions.assign_ids();
+
+        return Ok(transactions);
+    }
+
+    // for changing the file, under the data lock: upgrades a file written by
+    // an older version, and gives every transaction with a duplicate id a new
+    // one. either way the file is written back, so the ids stay the same from
+    // then on
+    pub fn upgrade_json_file<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
+        let mut value = schema::read(path)?;
+        let upgraded = schema::TRANSACTIONS.migrate(path, &mut value)?;
+        let mut transactions = Self::from_value(path, value)?;
+
+        if transactions.assign_ids() || upgraded {
+            transactions.to_json_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    fn from_value(path: &Path, value: serde_json::Value) -> Result<Self, Box<dyn Error>> {
+        let mut transactions: Self = serde_json::from_value(value)
+            .map_err(|e| format!("{} is invalid: {}", path.display(), e))?;
         transactions.transactions.sort();
 
         return Ok(transactions);
     }
 
+    // serializes everything before touching the file, then replaces it
+    // atomically, so a failure at any point leaves the old file intact
+    pub fn to_json_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let mut json = Vec::new();
+        self.to_json_writer(&mut json)?;
+        atomic_file::write(path, &json)?;
+
+        Ok(())
+    }
+
+    // gives each transaction without an id, or with the id of one before it,
+    // an id derived from what it holds and where it is, so that the same file
+    // always gets the same ids. returns whether any id was assigned
+    fn assign_ids(&mut self) -> bool {
+        let mut seen = HashSet::new();
+        let mut missing = Vec::new();
+        for (i, t) in self.transactions.iter().enumerate() {
+            if !t.id.is_assigned() || !seen.insert(t.id.clone()) {
+                missing.push(i);
+            }
+        }
+
+        for i in &missing {
+            let t = &self.transactions[*i];
+            let mut attempt = 0u32;
+            let id = loop {
+                let id = TransactionId::derive(&format!(
+                    "{} {} {} {} {} {}",
+                    t.date,
+                    t.amount.to_plain_string(),
+                    t.tag.to_dotted_string(),
+                    t.comment,
+                    i,
+                    attempt
+                ));
+                if !seen.contains(&id) {
+                    break id;
+                }
+                attempt += 1;
+            };
+            seen.insert(id.clone());
+            self.transactions[*i].id = id;
+        }
+
+        return !missing.is_empty();
+    }
+
+    pub fn new_id(&self) -> TransactionId {
+        loop {
+            let id = TransactionId::generate();
+            if self.transactions.iter().all(|t| t.id != id) {
+                return id;
+            }
+        }
+    }
+
     pub fn to_json_writer<W>(&self, w: W) -> Result<(), serde_json::Error>
     where
         W: Write,
     {
-        serde_json::to_writer_pretty(w, self)
+        let versioned = Versioned {
+            version: schema::TRANSACTIONS.current(),
+            data: self,
+        };
+        serde_json::to_writer_pretty(w, &versioned)
     }
 
     pub fn empty() -> Transactions {
@@ -47,6 +235,16 @@ impl Transactions {
         self.transactions.push(t);
         self.transactions.sort();
     }
+
+    pub fn position(&self, id: &TransactionId) -> Option<usize> {
+        self.transactions.iter().position(|t| t.id == *id)
+    }
+
+    pub fn matching(&self, selector: &Selector) -> Vec<usize> {
+        (0..self.transactions.len())
+            .filter(|i| selector.matches(&self.transactions[*i]))
+            .collect()
+    }
 }
 
 impl<'a> Transaction {
@@ -55,8 +253,126 @@ impl<'a> Transaction {
     }
 }
 
+// for tests: a transaction from the values it would have in a file
+#[cfg(test)]
+impl Transaction {
+    pub fn sample(date: &str, cents: i64, tag: &str, comment: &str) -> Transaction {
+        Transaction {
+            date: Date::from_str(date).unwrap(),
+            amount: Money::from_cents(cents),
+            tag: Tag::from_str(tag).unwrap(),
+            comment: comment.to_string(),
+            id: TransactionId::generate(),
+            bank_id: None,
+        }
+    }
+
+    // an empty id leaves it unassigned
+    pub fn with_id(self, id: &str) -> Transaction {
+        Transaction {
+            id: TransactionId::from_str(id).unwrap_or_default(),
+            ..self
+        }
+    }
+}
+
 impl Display for Transaction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{} ({} on {})", self.comment, self.amount, self.date)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scratch::ScratchDir;
+
+    #[test]
+    fn test_transaction_id() {
+        assert_eq!(
+            TransactionId::from_str("3F9A0C12").unwrap().to_string(),
+            "3f9a0c12"
+        );
+        assert!(TransactionId::from_str("3f9a0c1").is_err());
+        assert!(TransactionId::from_str("3f9a0c1g").is_err());
+
+        let id = TransactionId::generate();
+        assert_eq!(TransactionId::from_str(&id.to_string()).unwrap(), id);
+    }
+
+    #[test]
+    fn test_missing_and_duplicate_ids_are_assigned() {
+        let json = r#"{"transactions": [
+            {"date": "2025-09-01", "amount": "4.50", "tag": "coffee", "comment": ""},
+            {"date": "2025-09-01", "amount": "4.50", "tag": "coffee", "comment": "", "id": "0000abcd"},
+            {"date": "2025-09-02", "amount": "4.50", "tag": "coffee", "comment": "", "id": "0000abcd"}
+        ]}"#;
+        let mut transactions: Transactions = serde_json::from_str(json).unwrap();
+
+        assert!(transactions.assign_ids());
+        let ids: HashSet<_> = transactions.transactions.iter().map(|t| &t.id).collect();
+        assert_eq!(ids.len(), 3);
+        assert!(ids.iter().all(|id| id.is_assigned()));
+        assert!(!transactions.assign_ids());
+    }
+
+    #[test]
+    fn test_only_upgrading_writes_the_file() {
+        let directory = ScratchDir::new("upgrade");
+        let path = directory.join("transactions.json");
+        let old = r#"{"transactions": [
+            {"date": "2025-09-01", "amount": "4.50", "tag": "coffee", "comment": ""}
+        ]}"#;
+        std::fs::write(&path, old).unwrap();
+
+        let read = Transactions::from_json_file(&path).unwrap();
+        assert!(read.transactions[0].id.is_assigned());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), old);
+
+        Transactions::upgrade_json_file(&path).unwrap();
+        let value = schema::read(&path).unwrap();
+        assert_eq!(value["version"], schema::TRANSACTIONS.current());
+        assert_eq!(
+            std::fs::read_to_string(crate::atomic_file::version_backup_path(&path, 1)).unwrap(),
+            old
+        );
+    }
+
+    #[test]
+    fn test_ids_of_an_old_file_stay_the_same() {
+        use crate::lock::DataLock;
+        use crate::paths::Paths;
+        use crate::storage;
+
+        let directory = ScratchDir::new("old-ids");
+        let paths = Paths::new(directory.path());
+        std::fs::write(
+            paths.transactions(),
+            r#"{"transactions": [
+                {"date": "2025-09-01", "amount": "4.50", "tag": "coffee", "comment": ""},
+                {"date": "2025-09-01", "amount": "4.50", "tag": "coffee", "comment": ""},
+                {"date": "2025-09-02", "amount": "9.00", "tag": "food", "comment": "lunch"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let ids = |transactions: &Transactions| -> Vec<TransactionId> {
+            transactions
+                .transactions
+                .iter()
+                .map(|t| t.id.clone())
+                .collect()
+        };
+        let first = Transactions::from_json_file(paths.transactions()).unwrap();
+        let second = Transactions::from_json_file(paths.transactions()).unwrap();
+        assert_eq!(ids(&first), ids(&second));
+        assert_eq!(ids(&first).iter().collect::<HashSet<_>>().len(), 3);
+
+        // what list showed is what remove finds once the file is upgraded
+        let lock = DataLock::acquire(paths.lock()).unwrap();
+        let mut storage = storage::open_for_change(&paths, &lock).unwrap();
+        let removed = storage.delete(&[first.transactions[2].id.clone()]).unwrap();
+        assert_eq!(removed, vec![first.transactions[2].clone()]);
+        assert_eq!(ids(&storage.load().unwrap()), ids(&first)[..2].to_vec());
+    }
+}
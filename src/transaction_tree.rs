@@ -1,14 +1,28 @@
+use crate::colors;
 use crate::date::Date;
+use crate::forecast::Forecast;
 use crate::money::Money;
+use crate::period::Period;
+use crate::progress::Progress;
+use crate::settings;
 use crate::tag::{Tag, TagSlice};
 use crate::transaction::Transaction;
 use std::collections::BTreeMap;
-use std::collections::HashMap;
 use std::fmt::{self, Display};
+use thiserror::Error;
 
 #[derive(Debug)]
 pub struct TransactionTree<'a> {
     tree: BTreeMap<TagSlice<'a>, Category<'a>>,
+    period: Period,
+    as_of: Date,
+    forecast: Option<Forecast>,
+}
+
+#[derive(Error, Debug)]
+pub enum TreeError {
+    #[error("{0} has transactions but is also the parent of another tag")]
+    TransactionsOnParent(Tag),
 }
 
 #[derive(Debug)]
@@ -52,34 +66,32 @@ impl<'a> Category<'a> {
         }
     }
 
-    fn percent_for_month(&self) -> Option<i64> {
-        self.spent().checked_div(self.limit()?)
-    }
-
-    fn left_to_date(&self) -> Option<Money> {
-        // TODO: really this should validate that we are currently in the
-        // month corresponding to this TransactionTree, and should probably error
-        // or be none if we are not.
-
-        Money::left_to_date_in_month(Date::today(), self.limit()?, self.spent())
+    fn progress(&self, period: Period, as_of: Date) -> Option<Progress> {
+        Some(Progress::new(period, as_of, self.limit()?, self.spent()))
     }
 }
 
 impl<'a> TransactionTree<'a> {
     pub fn from_transactions_and_limits(
-        transactions: &'a Vec<Transaction>,
-        limits: &'a HashMap<Tag, Money>,
-    ) -> Result<TransactionTree<'a>, ()> {
-        // as you go through the transactions, as you go up the parents, ensure each parent has no transactions, otherwise it violates constraints
+        transactions: &'a [Transaction],
+        limits: &'a BTreeMap<Tag, Money>,
+        period: Period,
+        as_of: Date,
+    ) -> Result<TransactionTree<'a>, TreeError> {
+        // only leaves may have transactions, so a tag that has transactions
+        // can never show up as the parent of another tag. transactions outside
+        // the period, e.g. earlier in the month with weekly periods, do not count
 
         let mut map = BTreeMap::new();
 
-        for t in transactions {
+        for t in transactions.iter().filter(|t| period.contains(t.date)) {
             let leaf_tag = t.tag();
 
             let mut leaf = map.entry(leaf_tag).or_insert_with(Category::new_leaf);
             match &mut leaf {
-                Category::NonLeaf { .. } => todo!("Add an error here."),
+                Category::NonLeaf { .. } => {
+                    return Err(TreeError::TransactionsOnParent(leaf_tag.to_tag()));
+                }
                 Category::Leaf {
                     spent,
                     transactions,
@@ -91,11 +103,11 @@ impl<'a> TransactionTree<'a> {
             }
 
             for p in leaf_tag.parents() {
-                dbg!("Adding", p);
-
                 let mut non_leaf = map.entry(p).or_insert_with(Category::new_non_leaf);
                 match &mut non_leaf {
-                    Category::Leaf { .. } => todo!("Add error here"),
+                    Category::Leaf { .. } => {
+                        return Err(TreeError::TransactionsOnParent(p.to_tag()));
+                    }
                     Category::NonLeaf { spent, .. } => *spent += t.amount,
                 }
             }
@@ -112,11 +124,11 @@ impl<'a> TransactionTree<'a> {
             }
 
             for p in tag.parents() {
-                dbg!("Adding for limits", p);
-
                 let mut non_leaf = map.entry(p).or_insert_with(Category::new_non_leaf);
                 match &mut non_leaf {
-                    Category::Leaf { .. } => todo!("Add error here"),
+                    Category::Leaf { .. } => {
+                        return Err(TreeError::TransactionsOnParent(p.to_tag()));
+                    }
                     Category::NonLeaf { .. } => {}
                 }
             }
@@ -128,7 +140,45 @@ impl<'a> TransactionTree<'a> {
             }
         }
 
-        return Ok(TransactionTree { tree: map });
+        return Ok(TransactionTree {
+            tree: map,
+            period,
+            as_of,
+            forecast: None,
+        });
+    }
+
+    pub fn progress(&self, tag: TagSlice<'a>) -> Option<Progress> {
+        self.tree.get(&tag)?.progress(self.period, self.as_of)
+    }
+
+    pub fn spent(&self, tag: TagSlice<'a>) -> Option<Money> {
+        Some(self.tree.get(&tag)?.spent())
+    }
+
+    // every tag in the tree in order, with what was spent and its limit
+    pub fn summary(&self) -> impl Iterator<Item = (TagSlice<'a>, Money, Option<Money>)> {
+        self.tree
+            .iter()
+            .map(|(tag, category)| (*tag, category.spent(), category.limit()))
+    }
+
+    pub fn set_forecast(&mut self, forecast: Forecast) {
+        self.forecast = Some(forecast);
+    }
+
+    pub fn projected(&self, tag: TagSlice<'a>) -> Option<Money> {
+        self.forecast.as_ref()?.projected(tag)
+    }
+
+    // only worth flagging while there are days left to change course
+    pub fn projected_to_exceed(&self, tag: TagSlice<'a>) -> bool {
+        let limit = self.tree.get(&tag).and_then(Category::limit);
+
+        match (self.projected(tag), limit) {
+            (Some(projected), Some(limit)) => projected > limit && self.as_of < self.period.end(),
+            _ => false,
+        }
     }
 }
 
@@ -142,24 +192,38 @@ impl<'a> Display for TransactionTree<'a> {
 
             write!(f, "{}{}", " ".repeat(indentation), tag)?;
 
-            let left_to_date = category.left_to_date();
-            let percent_for_month = category.percent_for_month();
-            let limit = category.limit();
-
-            match (left_to_date, percent_for_month, limit) {
-                (Some(left), Some(percent), Some(limit)) => write!(
-                    f,
-                    ": {} left to date, {}% of {} used for the month",
-                    left, percent, limit
-                )?,
-                (_, _, _) => {}
+            let progress = category.progress(self.period, self.as_of);
+            match progress {
+                Some(progress) => write!(f, ": {}", progress)?,
+                None => write!(f, ": {} spent", category.spent())?,
             }
 
-            write!(f, "\n")?;
+            // the forecast knows about recurring items and past months, so it
+            // stands in for the straight-line projection whenever there is one
+            let projected = self
+                .projected(*tag)
+                .or_else(|| progress.and_then(|p| p.projected()));
+            if let Some(projected) = projected {
+                write!(f, ", projected {}", projected)?;
 
-            if let Category::Leaf { transactions, .. } = category {
-                for t in transactions.iter().take(3) {
+                if self.projected_to_exceed(*tag) {
                     write!(
+                        f,
+                        " {}(projected to exceed its limit){}",
+                        colors::red(),
+                        colors::reset()
+                    )?;
+                }
+            }
+
+            writeln!(f)?;
+
+            if let Category::Leaf { transactions, .. } = category {
+                for t in transactions
+                    .iter()
+                    .take(settings::get().display.transactions_per_leaf)
+                {
+                    writeln!(
                         f,
                         "{}• {}",
                         " ".repeat(indentation.checked_add(2).ok_or(fmt::Error)?),